@@ -0,0 +1,193 @@
+//! Compares `LocalStorageVec` with `Vec` and `SmallVec`, run with `cargo bench` from `bench/`.
+//!
+//! Every workload is measured for several inline capacities and element sizes, with element
+//! counts below, at, and above the inline capacity.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use smallvec::SmallVec;
+use stackvec::LocalStorageVec;
+
+/// An element of 64 bytes
+#[allow(dead_code)] // only ever moved around
+#[derive(Debug, Clone, Copy, Default)]
+struct Wide([u64; 8]);
+
+trait Element: Copy + Default {
+    fn new(i: usize) -> Self;
+}
+
+impl Element for u8 {
+    fn new(i: usize) -> Self {
+        i as u8
+    }
+}
+
+impl Element for u64 {
+    fn new(i: usize) -> Self {
+        i as u64
+    }
+}
+
+impl Element for Wide {
+    fn new(i: usize) -> Self {
+        Wide([i as u64; 8])
+    }
+}
+
+/// Element counts around the inline capacity `n`
+fn counts(n: usize) -> [usize; 3] {
+    [n / 2, n, 4 * n]
+}
+
+fn bench_workloads<T: Element, const N: usize>(c: &mut Criterion, type_name: &str) {
+    let mut group = c.benchmark_group(format!("push/{type_name}/N={N}"));
+    for count in counts(N) {
+        group.bench_with_input(BenchmarkId::new("Vec", count), &count, |b, &count| {
+            b.iter(|| {
+                let mut v = Vec::new();
+                (0..count).for_each(|i| v.push(T::new(i)));
+                black_box(v)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("SmallVec", count), &count, |b, &count| {
+            b.iter(|| {
+                let mut v: SmallVec<[T; N]> = SmallVec::new();
+                (0..count).for_each(|i| v.push(T::new(i)));
+                black_box(v)
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("LocalStorageVec", count),
+            &count,
+            |b, &count| {
+                b.iter(|| {
+                    let mut v: LocalStorageVec<T, N> = LocalStorageVec::new();
+                    (0..count).for_each(|i| v.push(T::new(i)));
+                    black_box(v)
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("extend/{type_name}/N={N}"));
+    for count in counts(N) {
+        let source: Vec<T> = (0..count).map(T::new).collect();
+
+        group.bench_with_input(BenchmarkId::new("Vec", count), &source, |b, source| {
+            b.iter(|| {
+                let mut v = Vec::new();
+                v.extend_from_slice(source);
+                black_box(v)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("SmallVec", count), &source, |b, source| {
+            b.iter(|| {
+                let mut v: SmallVec<[T; N]> = SmallVec::new();
+                v.extend_from_slice(source);
+                black_box(v)
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("LocalStorageVec", count),
+            &source,
+            |b, source| {
+                b.iter(|| {
+                    let mut v: LocalStorageVec<T, N> = LocalStorageVec::new();
+                    v.extend_from_slice(source);
+                    black_box(v)
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("iterate/{type_name}/N={N}"));
+    for count in counts(N) {
+        let source: Vec<T> = (0..count).map(T::new).collect();
+        let small: SmallVec<[T; N]> = source.iter().copied().collect();
+        let mut local: LocalStorageVec<T, N> = LocalStorageVec::new();
+        local.extend_from_slice(&source);
+
+        group.bench_with_input(BenchmarkId::new("Vec", count), &source, |b, v| {
+            b.iter(|| {
+                v.iter().for_each(|x| {
+                    black_box(x);
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("SmallVec", count), &small, |b, v| {
+            b.iter(|| {
+                v.iter().for_each(|x| {
+                    black_box(x);
+                })
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("LocalStorageVec", count),
+            &local,
+            |b, v| {
+                b.iter(|| {
+                    v.iter().for_each(|x| {
+                        black_box(x);
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+
+    // the cost of the push that moves a full inline buffer to the heap
+    let mut group = c.benchmark_group(format!("spill/{type_name}/N={N}"));
+    let full: Vec<T> = (0..N).map(T::new).collect();
+
+    group.bench_function("Vec", |b| {
+        b.iter_batched(
+            || full.clone(),
+            |mut v| {
+                v.push(T::new(N));
+                v
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("SmallVec", |b| {
+        b.iter_batched(
+            || full.iter().copied().collect::<SmallVec<[T; N]>>(),
+            |mut v| {
+                v.push(T::new(N));
+                v
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("LocalStorageVec", |b| {
+        b.iter_batched(
+            || {
+                let mut v: LocalStorageVec<T, N> = LocalStorageVec::new();
+                v.extend_from_slice(&full);
+                v
+            },
+            |mut v| {
+                v.push(T::new(N));
+                v
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    bench_workloads::<u8, 16>(c, "u8");
+    bench_workloads::<u8, 64>(c, "u8");
+    bench_workloads::<u64, 4>(c, "u64");
+    bench_workloads::<u64, 16>(c, "u64");
+    bench_workloads::<Wide, 4>(c, "Wide");
+    bench_workloads::<Wide, 16>(c, "Wide");
+}
+
+criterion_group!(compare, benches);
+criterion_main!(compare);
@@ -0,0 +1,73 @@
+//! Applies the operations encoded in the input to both a `LocalStorageVec` and a `Vec`, and
+//! checks that they behave identically.
+//!
+//! Run with `cargo fuzz run vec_oracle` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use stackvec::LocalStorageVec;
+
+/// Small, so that inputs move between the inline and heap representation often
+type Lsv = LocalStorageVec<u8, 4>;
+
+fuzz_target!(|data: &[u8]| {
+    let mut lsv = Lsv::new();
+    let mut vec: Vec<u8> = Vec::new();
+
+    let mut bytes = data.iter().copied();
+
+    while let Some(op) = bytes.next() {
+        // every op takes at most one argument byte
+        let arg = bytes.next().unwrap_or(0);
+
+        // indices are taken modulo the valid range, so no operation panics
+        let index = |len: usize| usize::from(arg) % (len + 1);
+
+        match op % 9 {
+            0 => {
+                lsv.push(arg);
+                vec.push(arg);
+            }
+            1 => assert_eq!(lsv.pop(), vec.pop()),
+            2 => {
+                let i = index(vec.len());
+                lsv.insert(i, arg);
+                vec.insert(i, arg);
+            }
+            3 if !vec.is_empty() => {
+                let i = index(vec.len() - 1);
+                assert_eq!(lsv.remove(i), vec.remove(i));
+            }
+            4 => {
+                let values = (0..arg % 8).map(|i| arg.wrapping_add(i));
+                lsv.extend(values.clone());
+                vec.extend(values);
+            }
+            5 => {
+                lsv.truncate(usize::from(arg));
+                vec.truncate(usize::from(arg));
+            }
+            6 => {
+                let clone = lsv.clone();
+                assert_eq!(clone, lsv);
+                lsv = clone;
+            }
+            7 => {
+                assert!(lsv.iter().eq(vec.iter()));
+                assert!(lsv.clone().into_iter().eq(vec.clone()));
+            }
+            8 => {
+                let (start, end) = (index(vec.len()), index(vec.len()));
+                let range = start.min(end)..start.max(end);
+                assert!(lsv.drain(range.clone()).eq(vec.drain(range)));
+            }
+            _ => {}
+        }
+
+        lsv.check_invariants();
+        assert_eq!(&lsv[..], &vec[..]);
+        assert_eq!(lsv.len(), vec.len());
+        assert!(lsv.capacity() >= lsv.len());
+    }
+});
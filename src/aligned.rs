@@ -0,0 +1,409 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    capacity_overflow, insert_index_out_of_bounds, metrics, move_inline, record_spill,
+    remove_index_out_of_bounds, LocalStorageVec,
+};
+
+/// Aligns the inline buffer of an [`AlignedLocalStorageVec`] to 16 bytes
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(align(16))]
+pub struct Align16;
+
+/// Aligns the inline buffer of an [`AlignedLocalStorageVec`] to 32 bytes
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(align(32))]
+pub struct Align32;
+
+/// Aligns the inline buffer of an [`AlignedLocalStorageVec`] to 64 bytes
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(align(64))]
+pub struct Align64;
+
+/// A `LocalStorageVec` whose inline buffer is aligned like `A` (e.g. [`Align32`]), so that
+/// `as_ptr()` can feed aligned SIMD loads while the elements are inline.
+///
+/// The heap buffer of a spilled vector is only aligned like `T`: use
+/// [`AlignedLocalStorageVec::is_inline`] to pick the aligned code path.
+///
+/// Converting from and to a `LocalStorageVec<T, N>` moves the inline buffer or hands over the
+/// heap buffer, so code can switch to this type only where the alignment matters.
+pub struct AlignedLocalStorageVec<T, const N: usize, A> {
+    repr: Repr<T, N, A>,
+}
+
+enum Repr<T, const N: usize, A> {
+    /// invariant: `len <= N`
+    Inline {
+        buf: Aligned<A, [T; N]>,
+        len: usize,
+    },
+    Heap(Vec<T>),
+}
+
+/// `value`, with at least the alignment of `A`
+#[repr(C)]
+struct Aligned<A, B> {
+    // zero-sized, so `value` is at offset 0 and the struct is aligned like both `A` and `B`
+    _align: [A; 0],
+    value: B,
+}
+
+impl<A, B> Aligned<A, B> {
+    fn new(value: B) -> Self {
+        Self { _align: [], value }
+    }
+}
+
+impl<T: Default, const N: usize, A> AlignedLocalStorageVec<T, N, A> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline {
+                buf: Aligned::new(std::array::from_fn(|_| T::default())),
+                len: 0,
+            },
+        }
+    }
+
+    /// An `AlignedLocalStorageVec` with 0 elements, but which has space for `capacity` elements
+    pub fn with_capacity(capacity: usize) -> Self {
+        LocalStorageVec::with_capacity(capacity).into()
+    }
+
+    pub fn push(&mut self, value: T) {
+        match &mut self.repr {
+            Repr::Inline { buf, len } if *len < N => {
+                buf.value[*len] = value;
+                *len += 1;
+            }
+            Repr::Inline { .. } => {
+                self.reserve(1);
+                self.push(value);
+            }
+            Repr::Heap(v) => {
+                let capacity = v.capacity();
+                v.push(value);
+                metrics::record_growth(capacity, v.capacity());
+            }
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline { len: 0, .. } => None,
+            Repr::Inline { buf, len } => {
+                *len -= 1;
+                Some(std::mem::take(&mut buf.value[*len]))
+            }
+            Repr::Heap(v) => v.pop(),
+        }
+    }
+
+    /// Inserts `element` at `index`, shifting all elements after it to the right.
+    ///
+    /// Panics if `index > len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the index is out of bounds")
+    )]
+    pub fn insert(&mut self, index: usize, element: T) {
+        let len = self.len();
+        if index > len {
+            insert_index_out_of_bounds(index, len);
+        }
+
+        self.push(element);
+        self[index..].rotate_right(1);
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// Panics if `index >= len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the index is out of bounds")
+    )]
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        if index >= len {
+            remove_index_out_of_bounds(index, len);
+        }
+
+        self[index..].rotate_left(1);
+        self.pop().unwrap()
+    }
+
+    /// Shortens the vector to `new_len` elements, dropping the rest.
+    ///
+    /// Has no effect if `new_len >= len`.
+    pub fn truncate(&mut self, new_len: usize) {
+        match &mut self.repr {
+            Repr::Inline { buf, len } if new_len < *len => {
+                // shorten first, so a panicking `drop` can't leave taken (default) values behind
+                let old_len = std::mem::replace(len, new_len);
+
+                for e in &mut buf.value[new_len..old_len] {
+                    drop(std::mem::take(e));
+                }
+            }
+            Repr::Inline { .. } => {}
+            Repr::Heap(v) => v.truncate(new_len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T, const N: usize, A> AlignedLocalStorageVec<T, N, A> {
+    /// Whether the elements are stored in the (aligned) inline buffer
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { .. } => N,
+            Repr::Heap(v) => v.capacity(),
+        }
+    }
+
+    /// Reserve capacity for at least `additional` more elements.
+    ///
+    /// If the elements no longer fit inline, the contents are moved to the heap.
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.repr {
+            Repr::Inline { len, .. } if additional <= N - *len => {}
+            Repr::Inline { len, .. } => {
+                let capacity = len
+                    .checked_add(additional)
+                    .unwrap_or_else(|| capacity_overflow());
+                self.spill(Vec::with_capacity(capacity));
+            }
+            Repr::Heap(v) => {
+                let capacity = v.capacity();
+                v.reserve(additional);
+                metrics::record_growth(capacity, v.capacity());
+            }
+        }
+    }
+
+    /// Move the inline elements into `v`, in the same way `LocalStorageVec` spills
+    fn spill(&mut self, mut v: Vec<T>) {
+        if let Repr::Inline { buf, len } = std::mem::replace(&mut self.repr, Repr::Heap(Vec::new()))
+        {
+            let unused = move_inline(buf.value, len, &mut v);
+            let heap_capacity = v.capacity();
+            self.repr = Repr::Heap(v);
+
+            // the unused slots are dropped last, so if that panics the vector is intact
+            drop(unused);
+
+            record_spill::<T>(len, N, heap_capacity);
+        }
+    }
+}
+
+impl<T: Default, const N: usize, A> Default for AlignedLocalStorageVec<T, N, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize, A> Clone for AlignedLocalStorageVec<T, N, A> {
+    fn clone(&self) -> Self {
+        let repr = match &self.repr {
+            Repr::Inline { buf, len } => Repr::Inline {
+                buf: Aligned::new(buf.value.clone()),
+                len: *len,
+            },
+            Repr::Heap(v) => Repr::Heap(v.clone()),
+        };
+
+        Self { repr }
+    }
+}
+
+/// Moves the inline buffer into the aligned one, or keeps the heap buffer
+impl<T, const N: usize, A> From<LocalStorageVec<T, N>> for AlignedLocalStorageVec<T, N, A> {
+    fn from(lsv: LocalStorageVec<T, N>) -> Self {
+        lsv.check_invariants();
+
+        let repr = match lsv {
+            LocalStorageVec::Stack { buf, len } => Repr::Inline {
+                buf: Aligned::new(buf),
+                len,
+            },
+            LocalStorageVec::Heap(v) => Repr::Heap(v),
+        };
+
+        Self { repr }
+    }
+}
+
+/// Moves the aligned inline buffer out, or keeps the heap buffer
+impl<T, const N: usize, A> From<AlignedLocalStorageVec<T, N, A>> for LocalStorageVec<T, N> {
+    fn from(aligned: AlignedLocalStorageVec<T, N, A>) -> Self {
+        match aligned.repr {
+            Repr::Inline { buf, len } => LocalStorageVec::Stack {
+                buf: buf.value,
+                len,
+            },
+            Repr::Heap(v) => LocalStorageVec::Heap(v),
+        }
+    }
+}
+
+impl<T, const N: usize, A> Deref for AlignedLocalStorageVec<T, N, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.repr {
+            Repr::Inline { buf, len } => &buf.value[..*len],
+            Repr::Heap(v) => v,
+        }
+    }
+}
+
+impl<T, const N: usize, A> DerefMut for AlignedLocalStorageVec<T, N, A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.repr {
+            Repr::Inline { buf, len } => &mut buf.value[..*len],
+            Repr::Heap(v) => v,
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, A> fmt::Debug for AlignedLocalStorageVec<T, N, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, A> PartialEq for AlignedLocalStorageVec<T, N, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize, A> Eq for AlignedLocalStorageVec<T, N, A> {}
+
+impl<T: Default, const N: usize, A> Extend<T> for AlignedLocalStorageVec<T, N, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|value| self.push(value));
+    }
+}
+
+impl<T, const N: usize, A> IntoIterator for AlignedLocalStorageVec<T, N, A> {
+    type Item = T;
+    type IntoIter = crate::IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        LocalStorageVec::from(self).into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_aligned<T>(ptr: *const T, align: usize) -> bool {
+        (ptr as usize).is_multiple_of(align)
+    }
+
+    #[test]
+    fn inline_buffer_is_aligned() {
+        // a `u8` before the vector would misalign it if the alignment weren't enforced
+        let pair: (u8, AlignedLocalStorageVec<f32, 8, Align32>) =
+            (0, AlignedLocalStorageVec::new());
+        assert!(is_aligned(pair.1.as_ptr(), 32));
+
+        let lsv: Box<AlignedLocalStorageVec<u8, 3, Align64>> = Box::default();
+        assert!(is_aligned(lsv.as_ptr(), 64));
+        assert_eq!(std::mem::align_of_val(&*lsv), 64);
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut lsv: AlignedLocalStorageVec<u32, 2, Align16> = AlignedLocalStorageVec::new();
+        lsv.extend([1, 2]);
+        assert!(lsv.is_inline());
+
+        lsv.push(3);
+        assert!(!lsv.is_inline());
+        lsv[0] = 10;
+        assert_eq!(&lsv[..], &[10, 2, 3]);
+        assert_eq!(format!("{lsv:?}"), "[10, 2, 3]");
+
+        assert_eq!(lsv.pop(), Some(3));
+        assert_eq!(lsv.pop(), Some(2));
+        assert_eq!(lsv.pop(), Some(10));
+        assert_eq!(lsv.pop(), None);
+    }
+
+    #[test]
+    fn insert_remove_truncate() {
+        let mut lsv: AlignedLocalStorageVec<u8, 4, Align32> = AlignedLocalStorageVec::new();
+        lsv.extend([1, 3]);
+
+        lsv.insert(1, 2);
+        assert_eq!(lsv.remove(0), 1);
+        assert_eq!(&lsv[..], &[2, 3]);
+
+        lsv.extend([4, 5, 6]);
+        assert!(!lsv.is_inline());
+
+        let copy = lsv.clone();
+        lsv.truncate(1);
+        assert_eq!(&lsv[..], &[2]);
+        assert_eq!(&copy[..], &[2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn spill_does_not_call_default() {
+        thread_local! {
+            static DEFAULTS_ALLOWED: std::cell::Cell<bool> = const { std::cell::Cell::new(true) };
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Fussy(u8);
+
+        impl Default for Fussy {
+            fn default() -> Self {
+                assert!(DEFAULTS_ALLOWED.get(), "default called");
+                Fussy(0)
+            }
+        }
+
+        let mut lsv: AlignedLocalStorageVec<Fussy, 2, Align16> = AlignedLocalStorageVec::new();
+        DEFAULTS_ALLOWED.set(false);
+
+        lsv.extend([Fussy(1), Fussy(2), Fussy(3)]);
+        assert!(!lsv.is_inline());
+        assert_eq!(&lsv[..], &[Fussy(1), Fussy(2), Fussy(3)]);
+    }
+
+    #[test]
+    fn conversions() {
+        let lsv: LocalStorageVec<f32, 4> = LocalStorageVec::from([1.0, 2.0]);
+
+        let aligned: AlignedLocalStorageVec<f32, 4, Align64> = lsv.into();
+        assert!(aligned.is_inline());
+        assert!(is_aligned(aligned.as_ptr(), 64));
+        assert_eq!(&aligned[..], &[1.0, 2.0]);
+
+        let lsv = LocalStorageVec::from(aligned);
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 2, .. }));
+
+        // a heap buffer is handed over in both directions
+        let lsv: LocalStorageVec<f32, 1> = LocalStorageVec::Heap(vec![1.0, 2.0, 3.0]);
+        let ptr = lsv.as_ptr();
+        let aligned: AlignedLocalStorageVec<f32, 1, Align32> = lsv.into();
+        assert_eq!(aligned.as_ptr(), ptr);
+        assert!(aligned.into_iter().eq([1.0, 2.0, 3.0]));
+    }
+}
@@ -0,0 +1,207 @@
+use std::alloc::Layout;
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::mem::MaybeUninit;
+use std::ptr::{self, NonNull};
+
+/// A bump allocator whose first chunk is an inline `[u8; N]` buffer.
+///
+/// Allocations are served from the inline buffer until it runs out, after which heap chunks of
+/// growing size are chained. Everything is freed at once by [`LocalStorageArena::reset`] or
+/// when the arena is dropped.
+///
+/// Like most bump allocators, the arena never runs the destructors of allocated values.
+pub struct LocalStorageArena<const N: usize> {
+    inline: UnsafeCell<[MaybeUninit<u8>; N]>,
+    /// heap chunks, the last one is the current chunk (the inline buffer if there are none)
+    ///
+    /// The chunks are owned (allocated with `Box::into_raw`, freed in `free_chunks`), but kept as
+    /// raw pointers: allocations are derived from these pointers without ever creating a `&mut` to
+    /// a whole chunk, which would invalidate the references already handed out into it.
+    chunks: RefCell<Vec<NonNull<[MaybeUninit<u8>]>>>,
+    /// bytes used in the current chunk
+    used: Cell<usize>,
+}
+
+impl<const N: usize> LocalStorageArena<N> {
+    pub fn new() -> Self {
+        Self {
+            inline: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            chunks: RefCell::new(Vec::new()),
+            used: Cell::new(0),
+        }
+    }
+
+    /// Move `value` into the arena
+    // the returned references point to disjoint memory, so handing out `&mut` from `&self` is fine
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+
+        // SAFETY: `ptr` is valid for writes of a `T`, and not handed out anywhere else
+        unsafe {
+            ptr.as_ptr().write(value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copy `slice` into the arena
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        let layout = Layout::for_value(slice);
+        let ptr = self.alloc_layout(layout).cast::<T>();
+
+        // SAFETY: `ptr` is valid for writes of `slice.len()` elements, and not handed out
+        // anywhere else
+        unsafe {
+            ptr::copy_nonoverlapping(slice.as_ptr(), ptr.as_ptr(), slice.len());
+            std::slice::from_raw_parts_mut(ptr.as_ptr(), slice.len())
+        }
+    }
+
+    /// Free all allocations, and go back to using the inline buffer
+    pub fn reset(&mut self) {
+        self.free_chunks();
+        self.used.set(0);
+    }
+
+    fn free_chunks(&mut self) {
+        for chunk in self.chunks.get_mut().drain(..) {
+            // SAFETY: the chunk was created by `Box::into_raw`, and `&mut self` guarantees that
+            // the allocations handed out from it are no longer in use
+            drop(unsafe { Box::from_raw(chunk.as_ptr()) });
+        }
+    }
+
+    /// Reserve memory for `layout` in the current chunk, chaining a new chunk if it doesn't fit
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        if let Some(ptr) = self.try_alloc_in_current(layout) {
+            return ptr;
+        }
+
+        let mut chunks = self.chunks.borrow_mut();
+
+        // double the previous chunk size, and leave room for aligning the allocation
+        let previous = chunks.last().map_or(N, |chunk| chunk.len());
+        let size = (2 * previous)
+            .max(64)
+            .max(layout.size() + layout.align() - 1);
+
+        let chunk = Box::into_raw(Box::<[u8]>::new_uninit_slice(size));
+        // SAFETY: `Box::into_raw` never returns a null pointer
+        chunks.push(unsafe { NonNull::new_unchecked(chunk) });
+        self.used.set(0);
+        drop(chunks);
+
+        self.try_alloc_in_current(layout)
+            .expect("a fresh chunk fits the allocation")
+    }
+
+    fn try_alloc_in_current(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let (base, capacity) = match self.chunks.borrow().last() {
+            Some(chunk) => (chunk.as_ptr().cast::<u8>(), chunk.len()),
+            None => (self.inline.get().cast::<u8>(), N),
+        };
+
+        let used = self.used.get();
+
+        // SAFETY: `used <= capacity`, so this stays within (or one past the end of) the chunk
+        let offset = used + unsafe { base.add(used) }.align_offset(layout.align());
+        let end = offset.checked_add(layout.size())?;
+
+        if end > capacity {
+            return None;
+        }
+
+        self.used.set(end);
+
+        // SAFETY: `offset <= capacity`, and pointers into a chunk are never null
+        Some(unsafe { NonNull::new_unchecked(base.add(offset)) })
+    }
+}
+
+impl<const N: usize> Drop for LocalStorageArena<N> {
+    fn drop(&mut self) {
+        self.free_chunks();
+    }
+}
+
+// SAFETY: the arena owns its chunks like a `Box` would, and the values allocated in them are
+// never accessed (or dropped) through the arena itself
+unsafe impl<const N: usize> Send for LocalStorageArena<N> {}
+
+impl<const N: usize> Default for LocalStorageArena<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inline_first() {
+        let arena: LocalStorageArena<64> = LocalStorageArena::new();
+
+        let a = arena.alloc(1u8);
+        let b = arena.alloc(2u64);
+        let c = arena.alloc_slice(&[3u16, 4, 5]);
+
+        *a += 10;
+        *b += 10;
+        c[0] += 10;
+
+        assert_eq!((*a, *b), (11, 12));
+        assert_eq!(c, &[13, 4, 5]);
+        assert_eq!((b as *mut u64).align_offset(std::mem::align_of::<u64>()), 0);
+        assert!(arena.chunks.borrow().is_empty());
+    }
+
+    #[test]
+    fn chains_heap_chunks() {
+        let mut arena: LocalStorageArena<16> = LocalStorageArena::new();
+
+        let values: Vec<&mut u64> = (0..100).map(|i| arena.alloc(i)).collect();
+        assert!(values.iter().enumerate().all(|(i, v)| **v == i as u64));
+        assert!(!arena.chunks.borrow().is_empty());
+
+        let big = arena.alloc([7u8; 1000]);
+        assert_eq!(big[999], 7);
+
+        arena.reset();
+        assert!(arena.chunks.borrow().is_empty());
+        assert_eq!(*arena.alloc(5u32), 5);
+    }
+
+    #[test]
+    fn references_survive_chunk_switches() {
+        let arena: LocalStorageArena<16> = LocalStorageArena::new();
+
+        let inline = arena.alloc(1u64);
+        let slice = arena.alloc_slice(&[2u32, 3]);
+
+        // each of these needs a new chunk
+        let first = arena.alloc([4u8; 100]);
+        let second = arena.alloc([5u8; 1000]);
+        assert_eq!(arena.chunks.borrow().len(), 2);
+
+        *inline += 10;
+        slice[1] += 10;
+        first[99] += 10;
+        second[999] += 10;
+
+        assert_eq!(*inline, 11);
+        assert_eq!(slice, &[2, 13]);
+        assert_eq!((first[0], first[99]), (4, 14));
+        assert_eq!((second[0], second[999]), (5, 15));
+    }
+
+    #[test]
+    fn zero_sized() {
+        let arena: LocalStorageArena<0> = LocalStorageArena::new();
+
+        assert_eq!(*arena.alloc(()), ());
+        assert!(arena.alloc_slice::<u32>(&[]).is_empty());
+        assert_eq!(*arena.alloc(3u8), 3);
+    }
+}
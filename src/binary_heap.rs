@@ -0,0 +1,200 @@
+use crate::LocalStorageVec;
+
+/// A max-heap (priority queue) stored in a `LocalStorageVec<T, N>`, so it only allocates once
+/// it holds more than `N` elements.
+#[derive(Debug, Clone)]
+pub struct LocalStorageBinaryHeap<T, const N: usize> {
+    // invariant: every element is >= its children, `vec[i]` having children `vec[2i + 1]` and `vec[2i + 2]`
+    vec: LocalStorageVec<T, N>,
+}
+
+impl<T: Ord + Default, const N: usize> LocalStorageBinaryHeap<T, N> {
+    pub fn new() -> Self {
+        Self {
+            vec: LocalStorageVec::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.vec.push(value);
+        self.sift_up(self.vec.len() - 1);
+    }
+
+    /// Removes and returns the greatest element
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.vec.len();
+        if len == 0 {
+            return None;
+        }
+
+        self.vec.swap(0, len - 1);
+        let max = self.vec.pop();
+        self.sift_down(0);
+
+        max
+    }
+
+    /// The elements in ascending order
+    pub fn into_sorted_vec(mut self) -> LocalStorageVec<T, N> {
+        // repeatedly move the maximum to the end of the shrinking heap prefix
+        for end in (1..self.vec.len()).rev() {
+            self.vec.swap(0, end);
+            sift_down_range(&mut self.vec[..end], 0);
+        }
+
+        self.vec
+    }
+}
+
+impl<T: Ord, const N: usize> LocalStorageBinaryHeap<T, N> {
+    /// The greatest element
+    pub fn peek(&self) -> Option<&T> {
+        self.vec.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// The elements in heap order
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// The elements in heap order
+    pub fn into_vec(self) -> LocalStorageVec<T, N> {
+        self.vec
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.vec[index] <= self.vec[parent] {
+                break;
+            }
+
+            self.vec.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, index: usize) {
+        sift_down_range(&mut self.vec, index);
+    }
+}
+
+/// Restore the heap property for the subtree rooted at `index`, assuming its children are heaps
+fn sift_down_range<T: Ord>(heap: &mut [T], mut index: usize) {
+    loop {
+        let left = 2 * index + 1;
+        let right = left + 1;
+
+        let mut largest = index;
+        if left < heap.len() && heap[left] > heap[largest] {
+            largest = left;
+        }
+        if right < heap.len() && heap[right] > heap[largest] {
+            largest = right;
+        }
+
+        if largest == index {
+            break;
+        }
+
+        heap.swap(index, largest);
+        index = largest;
+    }
+}
+
+impl<T: Ord + Default, const N: usize> Default for LocalStorageBinaryHeap<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds the heap in O(n)
+impl<T: Ord, const N: usize> From<LocalStorageVec<T, N>> for LocalStorageBinaryHeap<T, N> {
+    fn from(mut vec: LocalStorageVec<T, N>) -> Self {
+        for index in (0..vec.len() / 2).rev() {
+            sift_down_range(&mut vec, index);
+        }
+
+        Self { vec }
+    }
+}
+
+impl<T: Ord + Default, const N: usize> Extend<T> for LocalStorageBinaryHeap<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Ord + Default, const N: usize> FromIterator<T> for LocalStorageBinaryHeap<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = LocalStorageVec::new();
+        vec.extend(iter);
+        Self::from(vec)
+    }
+}
+
+/// Iterates in heap order, which is unspecified beyond the first element being the greatest
+impl<T, const N: usize> IntoIterator for LocalStorageBinaryHeap<T, N> {
+    type Item = T;
+    type IntoIter = crate::IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let mut heap: LocalStorageBinaryHeap<u8, 4> = LocalStorageBinaryHeap::new();
+
+        for x in [3, 1, 4, 1, 5, 9, 2, 6] {
+            heap.push(x);
+        }
+
+        assert_eq!(heap.peek(), Some(&9));
+
+        let mut popped = vec![];
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+
+        assert_eq!(popped, [9, 6, 5, 4, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn k_smallest() {
+        // keep the 3 smallest elements in a max-heap, evicting the largest
+        let mut heap: LocalStorageBinaryHeap<u8, 4> = LocalStorageBinaryHeap::new();
+
+        for x in [7, 3, 9, 1, 8, 2] {
+            heap.push(x);
+            if heap.len() > 3 {
+                heap.pop();
+            }
+        }
+
+        assert_eq!(&heap.into_sorted_vec()[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn from_iter_heapifies() {
+        let heap: LocalStorageBinaryHeap<u8, 8> = [2, 8, 5, 1, 9].into_iter().collect();
+
+        assert_eq!(heap.peek(), Some(&9));
+        assert_eq!(&heap.into_sorted_vec()[..], &[1, 2, 5, 8, 9]);
+    }
+}
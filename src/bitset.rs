@@ -0,0 +1,239 @@
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+
+use crate::LocalStorageVec;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A growable set of bit indices, stored in up to `N` inline `u64` words (so indices below
+/// `64 * N` never allocate) and moved to the heap beyond that.
+#[derive(Clone, Default)]
+pub struct LocalStorageBitSet<const N: usize> {
+    // bits past the last word are implicitly 0
+    words: LocalStorageVec<u64, N>,
+}
+
+impl<const N: usize> LocalStorageBitSet<N> {
+    pub fn new() -> Self {
+        Self {
+            words: LocalStorageVec::new(),
+        }
+    }
+
+    /// Sets bit `index`, returning whether it was previously unset
+    pub fn set(&mut self, index: usize) -> bool {
+        let (word, mask) = (index / WORD_BITS, 1 << (index % WORD_BITS));
+
+        if word >= self.words.len() {
+            let missing = word + 1 - self.words.len();
+            self.words.extend(std::iter::repeat_n(0, missing));
+        }
+
+        let was_unset = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+
+        was_unset
+    }
+
+    /// Clears bit `index`, returning whether it was previously set
+    pub fn clear(&mut self, index: usize) -> bool {
+        let (word, mask) = (index / WORD_BITS, 1 << (index % WORD_BITS));
+
+        match self.words.get_mut(word) {
+            Some(w) => {
+                let was_set = *w & mask != 0;
+                *w &= !mask;
+                was_set
+            }
+            None => false,
+        }
+    }
+
+    /// Whether bit `index` is set
+    pub fn test(&self, index: usize) -> bool {
+        let (word, mask) = (index / WORD_BITS, 1 << (index % WORD_BITS));
+
+        self.words.get(word).is_some_and(|w| w & mask != 0)
+    }
+
+    /// Clears all bits, keeping the storage
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// The number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// The indices of the set bits, in ascending order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut remaining = word;
+
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+
+                Some(i * WORD_BITS + bit)
+            })
+        })
+    }
+
+    /// The words with trailing zero words stripped, so equal sets compare equal
+    fn significant_words(&self) -> &[u64] {
+        let len = self
+            .words
+            .iter()
+            .rposition(|w| *w != 0)
+            .map_or(0, |i| i + 1);
+        &self.words[..len]
+    }
+}
+
+impl<const N: usize> PartialEq for LocalStorageBitSet<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.significant_words() == other.significant_words()
+    }
+}
+
+impl<const N: usize> Eq for LocalStorageBitSet<N> {}
+
+impl<const N: usize> fmt::Debug for LocalStorageBitSet<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.ones()).finish()
+    }
+}
+
+impl<const N: usize> FromIterator<usize> for LocalStorageBitSet<N> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<const N: usize> Extend<usize> for LocalStorageBitSet<N> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for index in iter {
+            self.set(index);
+        }
+    }
+}
+
+impl<const N: usize> BitAndAssign<&Self> for LocalStorageBitSet<N> {
+    fn bitand_assign(&mut self, rhs: &Self) {
+        let common = self.words.len().min(rhs.words.len());
+
+        for (a, b) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *a &= b;
+        }
+
+        self.words.truncate(common);
+    }
+}
+
+impl<const N: usize> BitOrAssign<&Self> for LocalStorageBitSet<N> {
+    fn bitor_assign(&mut self, rhs: &Self) {
+        for (a, b) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *a |= b;
+        }
+
+        if rhs.words.len() > self.words.len() {
+            let tail = &rhs.words[self.words.len()..];
+            self.words.extend_from_slice(tail);
+        }
+    }
+}
+
+impl<const N: usize> BitXorAssign<&Self> for LocalStorageBitSet<N> {
+    fn bitxor_assign(&mut self, rhs: &Self) {
+        for (a, b) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *a ^= b;
+        }
+
+        if rhs.words.len() > self.words.len() {
+            let tail = &rhs.words[self.words.len()..];
+            self.words.extend_from_slice(tail);
+        }
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($trait:ident, $method:ident, $assign_method:ident) => {
+        impl<const N: usize> $trait for &LocalStorageBitSet<N> {
+            type Output = LocalStorageBitSet<N>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                let mut result = self.clone();
+                result.$assign_method(rhs);
+                result
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, bitand_assign);
+impl_bit_op!(BitOr, bitor, bitor_assign);
+impl_bit_op!(BitXor, bitxor, bitxor_assign);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_clear_test() {
+        let mut bits: LocalStorageBitSet<1> = LocalStorageBitSet::new();
+
+        assert!(bits.set(3));
+        assert!(!bits.set(3));
+        assert!(bits.set(63));
+        assert!(matches!(bits.words, LocalStorageVec::Stack { .. }));
+
+        assert!(bits.set(64));
+        assert!(matches!(bits.words, LocalStorageVec::Heap(_)));
+
+        assert!(bits.test(3) && bits.test(64));
+        assert!(!bits.test(4) && !bits.test(1000));
+        assert_eq!(bits.count_ones(), 3);
+
+        assert!(bits.clear(3));
+        assert!(!bits.clear(3));
+        assert!(!bits.clear(1000));
+        assert_eq!(bits.ones().collect::<Vec<_>>(), [63, 64]);
+
+        bits.clear_all();
+        assert!(bits.is_empty());
+    }
+
+    #[test]
+    fn bit_ops() {
+        let a: LocalStorageBitSet<2> = [1, 2, 100].into_iter().collect();
+        let b: LocalStorageBitSet<2> = [2, 3].into_iter().collect();
+
+        assert_eq!((&a & &b).ones().collect::<Vec<_>>(), [2]);
+        assert_eq!((&a | &b).ones().collect::<Vec<_>>(), [1, 2, 3, 100]);
+        assert_eq!((&a ^ &b).ones().collect::<Vec<_>>(), [1, 3, 100]);
+        assert_eq!((&b | &a).ones().collect::<Vec<_>>(), [1, 2, 3, 100]);
+    }
+
+    #[test]
+    fn equality_ignores_trailing_words() {
+        let mut a: LocalStorageBitSet<2> = [1].into_iter().collect();
+        let b: LocalStorageBitSet<2> = [1].into_iter().collect();
+
+        a.set(100);
+        a.clear(100);
+
+        assert_eq!(a, b);
+        assert_eq!(format!("{a:?}"), "{1}");
+    }
+}
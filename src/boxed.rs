@@ -0,0 +1,282 @@
+use std::fmt;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+/// A box that stores its value inline when it is at most `N` bytes (and at most as aligned as a
+/// `usize`), and in a heap-allocated `Box` otherwise.
+///
+/// `T` can be unsized, e.g. `LocalStorageBox<dyn Fn() -> u32, 16>`: use the
+/// [`local_storage_box!`](crate::local_storage_box) macro to box a concrete value as a trait
+/// object, which stores it inline when the concrete type fits.
+pub struct LocalStorageBox<T: ?Sized, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+// the `Box<T>` variant also makes the auto traits (`Send`, `Sync`, ..) depend on `T`
+enum Repr<T: ?Sized, const N: usize> {
+    /// invariant: `buf` holds an initialized value, and `coerce` turns a pointer to that value
+    /// into a (possibly fat) pointer to `T`
+    Inline {
+        buf: InlineBuf<N>,
+        coerce: fn(*const u8) -> *const T,
+    },
+    Heap(Box<T>),
+}
+
+/// `N` bytes, aligned like a `usize`
+#[repr(C)]
+struct InlineBuf<const N: usize> {
+    _align: [usize; 0],
+    bytes: [MaybeUninit<u8>; N],
+}
+
+impl<const N: usize> InlineBuf<N> {
+    fn uninit() -> Self {
+        Self {
+            _align: [],
+            bytes: [MaybeUninit::uninit(); N],
+        }
+    }
+
+    fn as_ptr(&self) -> *const u8 {
+        self.bytes.as_ptr().cast()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.bytes.as_mut_ptr().cast()
+    }
+}
+
+/// Whether a `U` can be stored in an `InlineBuf<N>`
+const fn fits_inline<U, const N: usize>() -> bool {
+    mem::size_of::<U>() <= N && mem::align_of::<U>() <= mem::align_of::<InlineBuf<N>>()
+}
+
+impl<T, const N: usize> LocalStorageBox<T, N> {
+    pub fn new(value: T) -> Self {
+        // SAFETY: the identity is a valid coercion
+        unsafe { Self::new_unsize(value, |p| p) }
+    }
+
+    /// Move the value out of the box
+    pub fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+
+        // SAFETY: `this` is never dropped, so the value (or the box holding it) is moved out
+        // exactly once
+        match &this.repr {
+            Repr::Inline { buf, .. } => unsafe { buf.as_ptr().cast::<T>().read() },
+            Repr::Heap(boxed) => *unsafe { ptr::read(boxed) },
+        }
+    }
+}
+
+impl<T: ?Sized, const N: usize> LocalStorageBox<T, N> {
+    /// Box `value`, and view it as a `T` using `coerce`.
+    ///
+    /// Prefer the [`local_storage_box!`](crate::local_storage_box) macro, which supplies the
+    /// coercion.
+    ///
+    /// # Safety
+    ///
+    /// `coerce` must be an unsizing coercion (e.g. `|p| p`): it must return its argument with
+    /// only pointer metadata (a vtable or length) describing the `U` attached.
+    pub unsafe fn new_unsize<U>(value: U, coerce: fn(*const U) -> *const T) -> Self {
+        let repr = if fits_inline::<U, N>() {
+            let mut buf = InlineBuf::uninit();
+
+            // SAFETY: the buffer is large enough and sufficiently aligned for a `U`
+            unsafe { buf.as_mut_ptr().cast::<U>().write(value) };
+
+            // SAFETY: thin pointers are ABI-compatible, and `coerce` is only ever called with
+            // a pointer to the `U` in the buffer
+            let coerce = unsafe {
+                mem::transmute::<fn(*const U) -> *const T, fn(*const u8) -> *const T>(coerce)
+            };
+
+            Repr::Inline { buf, coerce }
+        } else {
+            let raw = Box::into_raw(Box::new(value));
+
+            // SAFETY: the caller guarantees `coerce` only attaches the metadata of the `U`
+            Repr::Heap(unsafe { Box::from_raw(coerce(raw) as *mut T) })
+        };
+
+        Self { repr }
+    }
+
+    /// Whether the value is stored inline (rather than on the heap)
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+}
+
+/// Box a value into a [`LocalStorageBox`], coercing it to the box's (possibly unsized) target type.
+///
+/// ```
+/// use stackvec::{local_storage_box, LocalStorageBox};
+///
+/// let offset = 10;
+/// let f: LocalStorageBox<dyn Fn(u32) -> u32, 16> = local_storage_box!(move |x| x + offset);
+///
+/// assert!(f.is_inline());
+/// assert_eq!(f(1), 11);
+/// ```
+#[macro_export]
+macro_rules! local_storage_box {
+    ($value:expr) => {
+        match $value {
+            // SAFETY: the identity closure only performs an (unsizing) coercion
+            value => unsafe { $crate::LocalStorageBox::new_unsize(value, |p| p) },
+        }
+    };
+}
+
+impl<T: ?Sized, const N: usize> Drop for LocalStorageBox<T, N> {
+    fn drop(&mut self) {
+        if let Repr::Inline { buf, coerce } = &mut self.repr {
+            // SAFETY: the buffer holds an initialized value, which is not used after this
+            unsafe { ptr::drop_in_place(coerce(buf.as_mut_ptr()) as *mut T) };
+        }
+    }
+}
+
+impl<T: ?Sized, const N: usize> Deref for LocalStorageBox<T, N> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.repr {
+            // SAFETY: the buffer holds an initialized value
+            Repr::Inline { buf, coerce } => unsafe { &*coerce(buf.as_ptr()) },
+            Repr::Heap(boxed) => boxed,
+        }
+    }
+}
+
+impl<T: ?Sized, const N: usize> DerefMut for LocalStorageBox<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.repr {
+            // SAFETY: the buffer holds an initialized value, and the pointer is derived from a
+            // mutable borrow of the buffer
+            Repr::Inline { buf, coerce } => unsafe { &mut *(coerce(buf.as_mut_ptr()) as *mut T) },
+            Repr::Heap(boxed) => boxed,
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for LocalStorageBox<T, N> {
+    fn clone(&self) -> Self {
+        Self::new(T::clone(self))
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, const N: usize> fmt::Debug for LocalStorageBox<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Default, const N: usize> Default for LocalStorageBox<T, N> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, const N: usize> From<T> for LocalStorageBox<T, N> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn inline_or_heap() {
+        let small: LocalStorageBox<u32, 8> = LocalStorageBox::new(42);
+        assert!(small.is_inline());
+        assert_eq!(*small, 42);
+
+        let large: LocalStorageBox<[u64; 4], 8> = LocalStorageBox::new([1, 2, 3, 4]);
+        assert!(!large.is_inline());
+        assert_eq!(large[3], 4);
+
+        // fits by size, but is too strictly aligned
+        let aligned: LocalStorageBox<u128, 16> = LocalStorageBox::new(7);
+        assert_eq!(
+            aligned.is_inline(),
+            mem::align_of::<u128>() <= mem::align_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn deref_mut_and_into_inner() {
+        let mut b: LocalStorageBox<String, 32> = LocalStorageBox::new(String::from("a"));
+        assert!(b.is_inline());
+
+        b.push('b');
+        let moved = b;
+        assert_eq!(moved.into_inner(), "ab");
+    }
+
+    #[test]
+    fn trait_objects() {
+        let offset = 10u32;
+        let mut fs: Vec<LocalStorageBox<dyn Fn(u32) -> u32, 8>> = vec![
+            local_storage_box!(move |x| x + offset),
+            local_storage_box!(|x| x * 2),
+            local_storage_box!(move |x| x + [1u64; 4].len() as u32 + offset),
+        ];
+
+        let big = [1u64; 4];
+        fs.push(local_storage_box!(move |x| x + big.len() as u32));
+
+        assert!(fs[0].is_inline() && fs[1].is_inline());
+        assert!(!fs[3].is_inline());
+        assert_eq!(fs.iter().map(|f| f(1)).collect::<Vec<_>>(), [11, 2, 15, 5]);
+
+        let mut debug: LocalStorageBox<dyn fmt::Debug, 8> = local_storage_box!(1u8);
+        assert_eq!(format!("{debug:?}"), "1");
+        debug = local_storage_box!([0u8; 9]);
+        assert!(!debug.is_inline());
+    }
+
+    #[test]
+    fn unsized_deref_mut() {
+        let mut slice: LocalStorageBox<[u16], 8> = local_storage_box!([1u16, 2, 3]);
+        assert!(slice.is_inline());
+
+        slice[1] = 20;
+        assert_eq!(&*slice, &[1, 20, 3]);
+
+        let mut s: LocalStorageBox<dyn fmt::Write, 32> = local_storage_box!(String::new());
+        write!(s, "{}", 42).unwrap();
+    }
+
+    #[test]
+    fn drops_exactly_once() {
+        let rc = Rc::new(());
+
+        let inline: LocalStorageBox<Rc<()>, 8> = LocalStorageBox::new(rc.clone());
+        let heap: LocalStorageBox<Rc<()>, 0> = LocalStorageBox::new(rc.clone());
+        assert_eq!(Rc::strong_count(&rc), 3);
+
+        drop(inline.clone());
+        drop(inline);
+        let inner = heap.into_inner();
+        assert_eq!(Rc::strong_count(&rc), 2);
+
+        drop(inner);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let inline: LocalStorageBox<dyn fmt::Debug, 8> = local_storage_box!(rc.clone());
+        let heap: LocalStorageBox<dyn fmt::Debug, 0> = local_storage_box!(rc.clone());
+        assert_eq!(Rc::strong_count(&rc), 3);
+
+        drop((inline, heap));
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
@@ -0,0 +1,116 @@
+use std::str::Utf8Error;
+
+use crate::{FromUtf8Error, LocalStorageVec};
+
+impl<const N: usize> LocalStorageVec<u8, N> {
+    /// View the contents as a `&str`, if they are valid UTF-8
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self)
+    }
+
+    /// View the contents as a `&mut str`, if they are valid UTF-8
+    pub fn as_str_mut(&mut self) -> Result<&mut str, Utf8Error> {
+        std::str::from_utf8_mut(self)
+    }
+
+    #[cfg(not(feature = "safe-only"))]
+    /// View the contents as a `&str` without checking that they are valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The contents must be valid UTF-8, see [`std::str::from_utf8_unchecked`].
+    pub unsafe fn as_str_unchecked(&self) -> &str {
+        // SAFETY: the caller guarantees the contents are valid UTF-8
+        unsafe { std::str::from_utf8_unchecked(self) }
+    }
+
+    #[cfg(not(feature = "safe-only"))]
+    /// View the contents as a `&mut str` without checking that they are valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The contents must be valid UTF-8, and must still be valid UTF-8 when the borrow ends,
+    /// see [`std::str::from_utf8_unchecked_mut`].
+    pub unsafe fn as_str_mut_unchecked(&mut self) -> &mut str {
+        // SAFETY: the caller guarantees the contents are valid UTF-8
+        unsafe { std::str::from_utf8_unchecked_mut(self) }
+    }
+}
+
+impl<const N: usize> From<&str> for LocalStorageVec<u8, N> {
+    fn from(value: &str) -> Self {
+        let mut lsv = Self::with_capacity(value.len());
+        lsv.extend_from_slice(value.as_bytes());
+        lsv
+    }
+}
+
+/// Strings that fit are copied inline; longer strings keep their heap buffer without copying
+impl<const N: usize> From<String> for LocalStorageVec<u8, N> {
+    fn from(value: String) -> Self {
+        if value.len() <= N {
+            Self::from(value.as_str())
+        } else {
+            LocalStorageVec::Heap(value.into_bytes())
+        }
+    }
+}
+
+/// Checks that the bytes are valid UTF-8. A spilled vector hands over its heap buffer without
+/// copying.
+impl<const N: usize> TryFrom<LocalStorageVec<u8, N>> for String {
+    type Error = FromUtf8Error<N>;
+
+    fn try_from(value: LocalStorageVec<u8, N>) -> Result<Self, Self::Error> {
+        match value {
+            LocalStorageVec::Heap(v) => String::from_utf8(v).map_err(|e| {
+                let error = e.utf8_error();
+                FromUtf8Error::new(LocalStorageVec::Heap(e.into_bytes()), error)
+            }),
+            LocalStorageVec::Stack { .. } => {
+                let result = value.as_str().map(String::from);
+                result.map_err(|error| FromUtf8Error::new(value, error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn as_str() {
+        let mut lsv: LocalStorageVec<u8, 8> = LocalStorageVec::new();
+        lsv.extend(*b"hello");
+
+        assert_eq!(lsv.as_str(), Ok("hello"));
+
+        lsv.as_str_mut().unwrap().make_ascii_uppercase();
+        assert_eq!(lsv.as_str(), Ok("HELLO"));
+
+        lsv.push(0xFF);
+        assert!(lsv.as_str().is_err());
+        assert!(lsv.as_str_mut().is_err());
+    }
+
+    #[test]
+    fn string_conversions() {
+        let lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from("abc");
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(String::try_from(lsv).unwrap(), "abc");
+
+        let string = String::from("hello");
+        let ptr = string.as_ptr();
+        let lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from(string);
+        assert_eq!(lsv.as_ptr(), ptr);
+        let string = String::try_from(lsv).unwrap();
+        assert_eq!(string.as_ptr(), ptr);
+
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from("ab");
+        lsv.push(0xFF);
+        let error = String::try_from(lsv).unwrap_err();
+        assert_eq!(error.utf8_error().valid_up_to(), 2);
+        assert_eq!(&error.into_bytes()[..], b"ab\xFF");
+    }
+}
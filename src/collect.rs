@@ -0,0 +1,57 @@
+use crate::{CapacityError, LocalStorageVec};
+
+/// Collect an iterator into a `LocalStorageVec`, naming the inline capacity with a turbofish:
+/// `iter.collect_lsv::<8>()`.
+pub trait CollectLocalStorageVec: Iterator + Sized {
+    /// Collect the items, spilling to the heap when there are more than `N`
+    fn collect_lsv<const N: usize>(self) -> LocalStorageVec<Self::Item, N>
+    where
+        Self::Item: Default,
+    {
+        let mut lsv = LocalStorageVec::new();
+        lsv.extend(self);
+        lsv
+    }
+
+    /// Collect the items inline, failing with the first item that doesn't fit when there are
+    /// more than `N`
+    fn try_collect_lsv<const N: usize>(
+        self,
+    ) -> Result<LocalStorageVec<Self::Item, N>, CapacityError<Self::Item>>
+    where
+        Self::Item: Default,
+    {
+        let mut lsv = LocalStorageVec::new();
+        for item in self {
+            lsv.try_push(item)?;
+        }
+
+        Ok(lsv)
+    }
+}
+
+impl<I: Iterator> CollectLocalStorageVec for I {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn collect_lsv() {
+        let lsv = (1..=3).map(|x| x * 10).collect_lsv::<4>();
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+        assert_eq!(&lsv[..], &[10, 20, 30]);
+
+        let lsv = (0..5).collect_lsv::<4>();
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+    }
+
+    #[test]
+    fn try_collect_lsv() {
+        let lsv = "abc".chars().try_collect_lsv::<3>().unwrap();
+        assert_eq!(&lsv[..], &['a', 'b', 'c']);
+
+        let error = "abcd".chars().try_collect_lsv::<3>().unwrap_err();
+        assert_eq!(error.into_inner(), 'd');
+    }
+}
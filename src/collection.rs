@@ -0,0 +1,129 @@
+use crate::{CapacityError, LocalStorageVec};
+
+/// The surface shared by `LocalStorageVec` and `Vec`, so code can be generic over the choice of
+/// storage.
+pub trait SmallCollection<T>: Sized {
+    /// An empty collection with room for at least `capacity` elements
+    fn with_capacity(capacity: usize) -> Self;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many elements fit before the collection spills or reallocates
+    fn capacity(&self) -> usize;
+
+    fn push(&mut self, value: T);
+
+    /// Appends `value` if it fits in the current capacity, without allocating
+    fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>>;
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn as_slice(&self) -> &[T];
+
+    fn as_mut_slice(&mut self) -> &mut [T];
+}
+
+impl<T: Default, const N: usize> SmallCollection<T> for LocalStorageVec<T, N> {
+    fn with_capacity(capacity: usize) -> Self {
+        LocalStorageVec::with_capacity(capacity)
+    }
+
+    fn len(&self) -> usize {
+        LocalStorageVec::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        LocalStorageVec::capacity(self)
+    }
+
+    fn push(&mut self, value: T) {
+        LocalStorageVec::push(self, value)
+    }
+
+    fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        LocalStorageVec::try_push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        LocalStorageVec::pop(self)
+    }
+
+    fn as_slice(&self) -> &[T] {
+        LocalStorageVec::as_slice(self)
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        LocalStorageVec::as_mut_slice(self)
+    }
+}
+
+impl<T> SmallCollection<T> for Vec<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+
+    fn push(&mut self, value: T) {
+        Vec::push(self, value)
+    }
+
+    fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.len() == self.capacity() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.push(value);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn as_slice(&self) -> &[T] {
+        Vec::as_slice(self)
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        Vec::as_mut_slice(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Fill up the current capacity, then double the last element
+    fn fill<C: SmallCollection<u32>>() -> C {
+        let mut collection = C::with_capacity(3);
+        let mut next = 1;
+        while collection.try_push(next).is_ok() {
+            next += 1;
+        }
+
+        let last = collection.pop().unwrap();
+        collection.push(2 * last);
+        collection
+    }
+
+    #[test]
+    fn generic_over_storage() {
+        let lsv: LocalStorageVec<u32, 4> = fill();
+        assert_eq!(lsv.as_slice(), &[1, 2, 3, 8]);
+
+        let vec: Vec<u32> = fill();
+        assert_eq!(vec.len(), vec.capacity());
+        assert_eq!(vec.last(), Some(&(2 * vec.len() as u32)));
+    }
+}
@@ -0,0 +1,111 @@
+use std::ops::Deref;
+
+use crate::LocalStorageVec;
+
+/// A clone-on-write slice: either borrowed, or owned in a `LocalStorageVec<T, N>` (which is
+/// itself inline or on the heap).
+///
+/// Taking ownership of a borrowed slice of at most `N` elements copies it inline, so small
+/// edited copies don't allocate.
+#[derive(Debug, Clone)]
+pub enum LocalStorageCow<'a, T, const N: usize> {
+    Borrowed(&'a [T]),
+    Owned(LocalStorageVec<T, N>),
+}
+
+impl<'a, T: Clone + Default, const N: usize> LocalStorageCow<'a, T, N> {
+    /// Mutable access to the owned data, copying a borrowed slice first
+    pub fn to_mut(&mut self) -> &mut LocalStorageVec<T, N> {
+        if let LocalStorageCow::Borrowed(slice) = *self {
+            *self = LocalStorageCow::Owned(to_owned(slice));
+        }
+
+        match self {
+            LocalStorageCow::Owned(owned) => owned,
+            LocalStorageCow::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    /// The owned data, copying a borrowed slice first
+    pub fn into_owned(self) -> LocalStorageVec<T, N> {
+        match self {
+            LocalStorageCow::Borrowed(slice) => to_owned(slice),
+            LocalStorageCow::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<T, const N: usize> LocalStorageCow<'_, T, N> {
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, LocalStorageCow::Borrowed(_))
+    }
+
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+}
+
+/// Copies inline if `slice` fits, or into a heap buffer of exactly the right size otherwise
+fn to_owned<T: Clone + Default, const N: usize>(slice: &[T]) -> LocalStorageVec<T, N> {
+    let mut owned = LocalStorageVec::with_capacity(slice.len());
+    owned.extend_from_slice(slice);
+    owned
+}
+
+impl<T, const N: usize> Deref for LocalStorageCow<'_, T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            LocalStorageCow::Borrowed(slice) => slice,
+            LocalStorageCow::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<'a, T, const N: usize> From<&'a [T]> for LocalStorageCow<'a, T, N> {
+    fn from(slice: &'a [T]) -> Self {
+        LocalStorageCow::Borrowed(slice)
+    }
+}
+
+impl<T, const N: usize> From<LocalStorageVec<T, N>> for LocalStorageCow<'_, T, N> {
+    fn from(owned: LocalStorageVec<T, N>) -> Self {
+        LocalStorageCow::Owned(owned)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn to_mut_copies_inline() {
+        let input = [1u8, 2, 3];
+        let mut cow: LocalStorageCow<u8, 4> = LocalStorageCow::from(&input[..]);
+
+        assert!(cow.is_borrowed());
+        assert_eq!(&cow[..], &[1, 2, 3]);
+
+        cow.to_mut().push(4);
+        assert!(cow.is_owned());
+        assert_eq!(&cow[..], &[1, 2, 3, 4]);
+        assert!(matches!(
+            cow,
+            LocalStorageCow::Owned(LocalStorageVec::Stack { .. })
+        ));
+
+        // the input is untouched
+        assert_eq!(input, [1, 2, 3]);
+    }
+
+    #[test]
+    fn into_owned_spills_large_slices() {
+        let input = [1u8, 2, 3, 4, 5];
+        let cow: LocalStorageCow<u8, 4> = LocalStorageCow::Borrowed(&input);
+
+        let owned = cow.into_owned();
+        assert!(matches!(owned, LocalStorageVec::Heap(_)));
+        assert_eq!(owned.capacity(), 5);
+    }
+}
@@ -0,0 +1,818 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Index, IndexMut, Range, RangeBounds};
+
+use crate::slice_range;
+
+/// A double-ended queue that stores up to `N` elements inline in a ring buffer, and moves to a
+/// heap-allocated `VecDeque` when it grows beyond that.
+///
+/// A deque created with [`LocalStorageVecDeque::bounded`] instead never leaves the inline buffer:
+/// pushing onto a full bounded deque evicts the element at the other end.
+#[derive(Clone)]
+pub struct LocalStorageVecDeque<T, const N: usize> {
+    repr: Repr<T, N>,
+    bounded: bool,
+}
+
+#[derive(Clone)]
+enum Repr<T, const N: usize> {
+    /// The live elements are `buf[head]`, `buf[(head + 1) % N]`, .. (`len` of them)
+    Stack {
+        buf: [T; N],
+        head: usize,
+        len: usize,
+    },
+    Heap(VecDeque<T>),
+}
+
+impl<T: Default, const N: usize> LocalStorageVecDeque<T, N> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Stack {
+                buf: std::array::from_fn(|_| T::default()),
+                head: 0,
+                len: 0,
+            },
+            bounded: false,
+        }
+    }
+
+    /// An empty deque that holds at most `N` elements and never allocates.
+    ///
+    /// When full, `push_back` evicts and returns the front (oldest) element, and `push_front`
+    /// evicts and returns the back element.
+    pub fn bounded() -> Self {
+        Self {
+            bounded: true,
+            ..Self::new()
+        }
+    }
+
+    /// A `LocalStorageVecDeque` with 0 elements, but which has space for `capacity` elements
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            Self {
+                repr: Repr::Heap(VecDeque::with_capacity(capacity)),
+                bounded: false,
+            }
+        }
+    }
+
+    /// Appends `value` to the back.
+    ///
+    /// Returns the evicted front element when the deque is bounded and full, otherwise `None`.
+    pub fn push_back(&mut self, value: T) -> Option<T> {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } if *len < N => {
+                buf[(*head + *len) % N] = value;
+                *len += 1;
+                None
+            }
+            Repr::Stack { .. } if self.bounded => {
+                if N == 0 {
+                    return Some(value);
+                }
+
+                let evicted = self.pop_front();
+                self.push_back(value);
+                evicted
+            }
+            Repr::Stack { .. } => {
+                self.spill().push_back(value);
+                None
+            }
+            Repr::Heap(v) => {
+                v.push_back(value);
+                None
+            }
+        }
+    }
+
+    /// Prepends `value` to the front.
+    ///
+    /// Returns the evicted back element when the deque is bounded and full, otherwise `None`.
+    pub fn push_front(&mut self, value: T) -> Option<T> {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } if *len < N => {
+                *head = (*head + N - 1) % N;
+                buf[*head] = value;
+                *len += 1;
+                None
+            }
+            Repr::Stack { .. } if self.bounded => {
+                if N == 0 {
+                    return Some(value);
+                }
+
+                let evicted = self.pop_back();
+                self.push_front(value);
+                evicted
+            }
+            Repr::Stack { .. } => {
+                self.spill().push_front(value);
+                None
+            }
+            Repr::Heap(v) => {
+                v.push_front(value);
+                None
+            }
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } if *len > 0 => {
+                // shrink first, like `LocalStorageVec::pop`: if `T::default` panics the element
+                // is leaked rather than left in the deque as a live placeholder
+                let front = std::mem::replace(head, (*head + 1) % N);
+                *len -= 1;
+                Some(std::mem::take(&mut buf[front]))
+            }
+            Repr::Stack { .. } => None,
+            Repr::Heap(v) => v.pop_front(),
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } if *len > 0 => {
+                *len -= 1;
+                Some(std::mem::take(&mut buf[(*head + *len) % N]))
+            }
+            Repr::Stack { .. } => None,
+            Repr::Heap(v) => v.pop_back(),
+        }
+    }
+
+    /// Removes all elements
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+    }
+
+    /// Removes the elements in `range` (counting from the front), yielding them. Elements that
+    /// are not yielded are dropped when the `Drain` is.
+    ///
+    /// Panics if the range is out of bounds.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the range is out of bounds")
+    )]
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, N> {
+        let Range { start, end } = slice_range(range, self.len());
+
+        Drain {
+            deque: self,
+            start,
+            next: start,
+            back: end,
+            end,
+        }
+    }
+
+    /// Removes and drops the elements in `start..end`, which must be in bounds
+    fn remove_range(&mut self, start: usize, end: usize) {
+        match &mut self.repr {
+            Repr::Stack { .. } => {
+                // move the range to the end, then drop it from there
+                self.make_contiguous()[start..].rotate_left(end - start);
+
+                if let Repr::Stack { buf, len, .. } = &mut self.repr {
+                    // shorten first, like `LocalStorageVec::truncate`: a panicking `drop` can't
+                    // leave taken (default) values behind as live elements
+                    let old_len = std::mem::replace(len, *len - (end - start));
+
+                    for e in &mut buf[*len..old_len] {
+                        drop(std::mem::take(e));
+                    }
+                }
+            }
+            Repr::Heap(v) => {
+                v.drain(start..end);
+            }
+        }
+    }
+
+    /// Move the inline elements to the heap (in order), and return the new heap storage
+    fn spill(&mut self) -> &mut VecDeque<T> {
+        // take ownership of the array so the elements are moved, not replaced by `T::default`
+        // (which could panic halfway through and leave the deque half-moved)
+        let repr = std::mem::replace(&mut self.repr, Repr::Heap(VecDeque::new()));
+
+        self.repr = match repr {
+            Repr::Stack { buf, head, len } => {
+                let mut v = Vec::with_capacity(2 * N.max(1));
+                v.extend(buf);
+
+                let mut v = VecDeque::from(v);
+                v.rotate_left(head);
+                v.truncate(len);
+
+                Repr::Heap(v)
+            }
+            heap => heap,
+        };
+
+        match &mut self.repr {
+            Repr::Heap(v) => v,
+            Repr::Stack { .. } => unreachable!(),
+        }
+    }
+}
+
+impl<T, const N: usize> LocalStorageVecDeque<T, N> {
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Stack { len, .. } => *len,
+            Repr::Heap(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.repr {
+            Repr::Stack { .. } => N,
+            Repr::Heap(v) => v.capacity(),
+        }
+    }
+
+    /// Whether this deque evicts elements instead of spilling, see [`LocalStorageVecDeque::bounded`]
+    pub fn is_bounded(&self) -> bool {
+        self.bounded
+    }
+
+    /// The element at `index` (counting from the front), or `None` if out of bounds
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match &self.repr {
+            Repr::Stack { buf, head, len } if index < *len => Some(&buf[(*head + index) % N]),
+            Repr::Stack { .. } => None,
+            Repr::Heap(v) => v.get(index),
+        }
+    }
+
+    /// The element at `index` (counting from the front), or `None` if out of bounds
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } if index < *len => Some(&mut buf[(*head + index) % N]),
+            Repr::Stack { .. } => None,
+            Repr::Heap(v) => v.get_mut(index),
+        }
+    }
+
+    /// The contents as two slices: front to back, the first slice followed by the second
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        match &self.repr {
+            Repr::Stack { buf, head, len } => {
+                let (head, len) = (*head, *len);
+
+                if head + len <= N {
+                    (&buf[head..head + len], &[])
+                } else {
+                    let (wrapped, front) = buf.split_at(head);
+                    (front, &wrapped[..head + len - N])
+                }
+            }
+            Repr::Heap(v) => v.as_slices(),
+        }
+    }
+
+    /// The contents as two mutable slices: front to back, the first slice followed by the second
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } => {
+                let (head, len) = (*head, *len);
+
+                if head + len <= N {
+                    (&mut buf[head..head + len], &mut [])
+                } else {
+                    let (wrapped, front) = buf.split_at_mut(head);
+                    (front, &mut wrapped[..head + len - N])
+                }
+            }
+            Repr::Heap(v) => v.as_mut_slices(),
+        }
+    }
+
+    /// Rearrange the storage so the contents are one contiguous slice, and return that slice
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        match &mut self.repr {
+            Repr::Stack { buf, head, len } => {
+                buf.rotate_left(*head);
+                *head = 0;
+                &mut buf[..*len]
+            }
+            Repr::Heap(v) => v.make_contiguous(),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+
+    /// Iterate over the elements in `range` (counting from the front).
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_, T> {
+        let Range { start, end } = slice_range(range, self.len());
+        let (front, back) = self.as_slices();
+        let (front, back) = split_range(front, back, start, end);
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    /// Iterate mutably over the elements in `range` (counting from the front).
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn range_mut<R: RangeBounds<usize>>(&mut self, range: R) -> IterMut<'_, T> {
+        let Range { start, end } = slice_range(range, self.len());
+        let (front, back) = self.as_mut_slices();
+
+        // same split as `split_range`, but on mutable slices
+        let (front, back) = if start >= front.len() {
+            let offset = front.len();
+            (&mut back[start - offset..end - offset], &mut [][..])
+        } else if end <= front.len() {
+            (&mut front[start..end], &mut [][..])
+        } else {
+            let offset = front.len();
+            (&mut front[start..], &mut back[..end - offset])
+        };
+
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len().checked_sub(1)?)
+    }
+}
+
+/// The parts of the two halves `front` and `back` that hold the elements `start..end`
+fn split_range<'a, T>(
+    front: &'a [T],
+    back: &'a [T],
+    start: usize,
+    end: usize,
+) -> (&'a [T], &'a [T]) {
+    let offset = front.len();
+
+    if start >= offset {
+        (&back[start - offset..end - offset], &[])
+    } else if end <= offset {
+        (&front[start..end], &[])
+    } else {
+        (&front[start..], &back[..end - offset])
+    }
+}
+
+impl<T, const N: usize> Index<usize> for LocalStorageVecDeque<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        let len = self.len();
+        self.get(index).unwrap_or_else(|| {
+            panic!("index out of bounds: the len is {len} but the index is {index}")
+        })
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for LocalStorageVecDeque<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let len = self.len();
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!("index out of bounds: the len is {len} but the index is {index}")
+        })
+    }
+}
+
+impl<T: Default, const N: usize> Default for LocalStorageVecDeque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for LocalStorageVecDeque<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// both the inline ring buffer and `VecDeque` are (at most) two slices, so iteration
+// chains the iterators of those two slices
+
+/// Iterator over references to the elements of a [`LocalStorageVecDeque`], front to back
+pub struct Iter<'a, T> {
+    front: std::slice::Iter<'a, T>,
+    back: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.front.len() + self.back.len();
+        (n, Some(n))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// Iterator over mutable references to the elements of a [`LocalStorageVecDeque`], front to back
+pub struct IterMut<'a, T> {
+    front: std::slice::IterMut<'a, T>,
+    back: std::slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.front.len() + self.back.len();
+        (n, Some(n))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// Owned iterator over the elements of a [`LocalStorageVecDeque`], front to back
+pub struct IntoIter<T, const N: usize> {
+    inner: LocalStorageVecDeque<T, N>,
+}
+
+impl<T: Default, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.inner.len(), Some(self.inner.len()))
+    }
+}
+
+impl<T: Default, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.pop_back()
+    }
+}
+
+impl<T: Default, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T: Default, const N: usize> IntoIterator for LocalStorageVecDeque<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { inner: self }
+    }
+}
+
+/// Draining iterator of [`LocalStorageVecDeque::drain`]
+pub struct Drain<'a, T: Default, const N: usize> {
+    deque: &'a mut LocalStorageVecDeque<T, N>,
+    /// the start of the drained range
+    start: usize,
+    /// the next element to yield from the front
+    next: usize,
+    /// one past the next element to yield from the back
+    back: usize,
+    /// the end of the drained range
+    end: usize,
+}
+
+impl<T: Default, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.back {
+            return None;
+        }
+
+        let value = std::mem::take(&mut self.deque[self.next]);
+        self.next += 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Default, const N: usize> DoubleEndedIterator for Drain<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.next == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(std::mem::take(&mut self.deque[self.back]))
+    }
+}
+
+impl<T: Default, const N: usize> ExactSizeIterator for Drain<'_, T, N> {}
+
+impl<T: Default, const N: usize> Drop for Drain<'_, T, N> {
+    fn drop(&mut self) {
+        self.deque.remove_range(self.start, self.end);
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a LocalStorageVecDeque<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut LocalStorageVecDeque<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_pop_both_ends() {
+        let mut dq: LocalStorageVecDeque<u8, 4> = LocalStorageVecDeque::new();
+
+        dq.push_back(2);
+        dq.push_front(1);
+        dq.push_back(3);
+
+        assert_eq!(dq.len(), 3);
+        assert_eq!(dq.front(), Some(&1));
+        assert_eq!(dq.back(), Some(&3));
+
+        assert_eq!(dq.pop_front(), Some(1));
+        assert_eq!(dq.pop_back(), Some(3));
+        assert_eq!(dq.pop_back(), Some(2));
+        assert_eq!(dq.pop_back(), None);
+        assert_eq!(dq.pop_front(), None);
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut dq: LocalStorageVecDeque<u8, 3> = LocalStorageVecDeque::new();
+
+        for i in 0..10 {
+            dq.push_back(i);
+            dq.push_back(i + 100);
+            assert_eq!(dq.pop_front(), Some(i));
+            assert_eq!(dq.pop_front(), Some(i + 100));
+        }
+
+        assert!(matches!(dq.repr, Repr::Stack { .. }));
+    }
+
+    #[test]
+    fn slices_and_iter() {
+        let mut dq: LocalStorageVecDeque<u8, 5> = LocalStorageVecDeque::new();
+        dq.push_back(3);
+        dq.push_back(4);
+        dq.push_front(2);
+        dq.push_front(1);
+
+        assert_eq!(dq.as_slices(), (&[1, 2][..], &[3, 4][..]));
+        assert_eq!(dq.iter().rev().copied().collect::<Vec<_>>(), [4, 3, 2, 1]);
+        assert_eq!(dq.iter().len(), 4);
+
+        for x in &mut dq {
+            *x *= 10;
+        }
+
+        assert_eq!(dq.into_iter().collect::<Vec<_>>(), [10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn make_contiguous_and_sort() {
+        let mut dq: LocalStorageVecDeque<u8, 5> = LocalStorageVecDeque::new();
+        dq.push_back(1);
+        dq.push_back(3);
+        dq.push_front(4);
+        dq.push_front(2);
+
+        dq.make_contiguous().sort();
+        assert_eq!(dq.as_slices(), (&[1, 2, 3, 4][..], &[][..]));
+        assert_eq!(dq.make_contiguous().binary_search(&3), Ok(2));
+
+        dq.push_front(0);
+        assert_eq!(dq.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn bounded_evicts() {
+        let mut dq: LocalStorageVecDeque<u8, 3> = LocalStorageVecDeque::bounded();
+
+        assert_eq!(dq.push_back(1), None);
+        assert_eq!(dq.push_back(2), None);
+        assert_eq!(dq.push_back(3), None);
+        assert_eq!(dq.push_back(4), Some(1));
+        assert_eq!(dq.push_back(5), Some(2));
+        assert_eq!(dq.iter().copied().collect::<Vec<_>>(), [3, 4, 5]);
+
+        assert_eq!(dq.push_front(2), Some(5));
+        assert_eq!(dq.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert!(matches!(dq.repr, Repr::Stack { .. }));
+
+        let mut empty: LocalStorageVecDeque<u8, 0> = LocalStorageVecDeque::bounded();
+        assert_eq!(empty.push_back(1), Some(1));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn spill_preserves_order() {
+        let mut dq: LocalStorageVecDeque<u8, 3> = LocalStorageVecDeque::new();
+
+        dq.push_back(3);
+        dq.push_front(2);
+        dq.push_front(1);
+        assert!(matches!(dq.repr, Repr::Stack { .. }));
+
+        dq.push_front(0);
+        dq.push_back(4);
+        assert!(matches!(dq.repr, Repr::Heap(_)));
+
+        assert_eq!(format!("{dq:?}"), "[0, 1, 2, 3, 4]");
+    }
+
+    #[test]
+    fn spill_does_not_call_default() {
+        thread_local! {
+            static DEFAULTS_ALLOWED: std::cell::Cell<bool> = const { std::cell::Cell::new(true) };
+        }
+
+        struct Fussy(u8);
+
+        impl Default for Fussy {
+            fn default() -> Self {
+                assert!(DEFAULTS_ALLOWED.get(), "default called");
+                Fussy(0)
+            }
+        }
+
+        let mut dq: LocalStorageVecDeque<Fussy, 3> = LocalStorageVecDeque::new();
+        DEFAULTS_ALLOWED.set(false);
+
+        dq.push_back(Fussy(2));
+        dq.push_front(Fussy(1));
+        dq.push_back(Fussy(3));
+        dq.push_front(Fussy(0));
+
+        assert!(matches!(dq.repr, Repr::Heap(_)));
+        assert_eq!(dq.iter().map(|f| f.0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+    }
+
+    /// A deque with contents `1..=5` whose inline ring buffer wraps around
+    fn wrapped() -> LocalStorageVecDeque<u8, 6> {
+        let mut dq = LocalStorageVecDeque::new();
+        for value in [0, 0, 0, 1, 2, 3] {
+            dq.push_back(value);
+        }
+
+        for _ in 0..3 {
+            dq.pop_front();
+        }
+
+        dq.push_back(4);
+        dq.push_back(5);
+        assert!(!dq.as_slices().1.is_empty());
+        dq
+    }
+
+    #[test]
+    fn index() {
+        let mut dq = wrapped();
+        assert_eq!((dq[0], dq[4]), (1, 5));
+
+        dq[3] = 40;
+        assert!(dq.iter().eq(&[1, 2, 3, 40, 5]));
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 5 but the index is 5")]
+    fn index_out_of_bounds() {
+        let _ = wrapped()[5];
+    }
+
+    #[test]
+    fn range() {
+        let mut dq = wrapped();
+
+        assert!(dq.range(..).eq(&[1, 2, 3, 4, 5]));
+        assert!(dq.range(1..2).eq(&[2]));
+        assert!(dq.range(2..5).eq(&[3, 4, 5]));
+        assert!(dq.range(4..).eq(&[5]));
+        assert!(dq.range(1..4).rev().eq(&[4, 3, 2]));
+        assert_eq!(dq.range(3..3).len(), 0);
+
+        dq.range_mut(2..4).for_each(|x| *x *= 10);
+        assert!(dq.iter().eq(&[1, 2, 30, 40, 5]));
+    }
+
+    #[test]
+    fn drain() {
+        let mut dq = wrapped();
+        assert!(dq.drain(1..4).eq([2, 3, 4]));
+        assert!(dq.iter().eq(&[1, 5]));
+
+        let mut dq = wrapped();
+        let mut drain = dq.drain(..);
+        assert_eq!((drain.next(), drain.next_back()), (Some(1), Some(5)));
+        drop(drain);
+        assert!(dq.is_empty());
+
+        let mut dq = wrapped();
+        dq.push_back(6);
+        dq.push_back(7);
+        assert!(dq.drain(5..).rev().eq([7, 6]));
+        assert!(dq.iter().eq(&[1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn drain_panicking_drop() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        #[derive(Default)]
+        struct PanicOnDrop(bool, u8);
+
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                if self.0 {
+                    self.0 = false;
+                    panic!("drop panicked");
+                }
+            }
+        }
+
+        let mut dq: LocalStorageVecDeque<PanicOnDrop, 4> = LocalStorageVecDeque::new();
+        dq.push_back(PanicOnDrop(false, 1));
+        dq.push_back(PanicOnDrop(true, 2));
+        dq.push_back(PanicOnDrop(false, 3));
+
+        let result = catch_unwind(AssertUnwindSafe(|| drop(dq.drain(..2))));
+        assert!(result.is_err());
+
+        // the drained elements are gone, even though dropping one of them panicked
+        assert_eq!(dq.len(), 1);
+        assert_eq!(dq[0].1, 3);
+    }
+}
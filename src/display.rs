@@ -0,0 +1,57 @@
+use std::fmt;
+
+use crate::LocalStorageVec;
+
+/// Writes the elements of a slice separated by a string, created by
+/// [`LocalStorageVec::display_join`]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayJoin<'a, T> {
+    elements: &'a [T],
+    separator: &'a str,
+}
+
+impl<T: fmt::Display> fmt::Display for DisplayJoin<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut elements = self.elements.iter();
+
+        if let Some(first) = elements.next() {
+            // forward the formatter, so width/precision flags apply to every element
+            first.fmt(f)?;
+
+            for element in elements {
+                f.write_str(self.separator)?;
+                element.fmt(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Display the elements separated by `separator`, without building an intermediate `String`
+    pub fn display_join<'a>(&'a self, separator: &'a str) -> DisplayJoin<'a, T> {
+        DisplayJoin {
+            elements: self,
+            separator,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display_join() {
+        let mut lsv: LocalStorageVec<u32, 2> = LocalStorageVec::new();
+        assert_eq!(lsv.display_join(", ").to_string(), "");
+
+        lsv.push(1);
+        assert_eq!(lsv.display_join(", ").to_string(), "1");
+
+        lsv.extend([2, 3]);
+        assert_eq!(lsv.display_join(", ").to_string(), "1, 2, 3");
+        assert_eq!(format!("[{:02}]", lsv.display_join("|")), "[01|02|03]");
+    }
+}
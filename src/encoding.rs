@@ -0,0 +1,180 @@
+use std::fmt;
+
+use crate::{LocalStorageString, LocalStorageVec};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The error of `from_hex` and `from_base64`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// the input length is not a multiple of 2 (hex) or 4 (base64)
+    InvalidLength,
+    /// the byte at this index is not part of the encoding (or is misplaced padding)
+    InvalidByte(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength => f.write_str("invalid input length"),
+            DecodeError::InvalidByte(index) => write!(f, "invalid byte at index {index}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl<const N: usize> LocalStorageVec<u8, N> {
+    /// The contents as lowercase hex, stored inline if it fits in `M` bytes
+    pub fn to_hex<const M: usize>(&self) -> LocalStorageString<M> {
+        let mut hex = LocalStorageString::with_capacity(2 * self.len());
+
+        for byte in self.iter() {
+            hex.push(HEX_DIGITS[usize::from(byte >> 4)] as char);
+            hex.push(HEX_DIGITS[usize::from(byte & 0xF)] as char);
+        }
+
+        hex
+    }
+
+    /// Decode (upper- or lowercase) hex
+    pub fn from_hex(hex: &str) -> Result<Self, DecodeError> {
+        let hex = hex.as_bytes();
+        if !hex.len().is_multiple_of(2) {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let digit = |index: usize| match hex[index] {
+            b @ b'0'..=b'9' => Ok(b - b'0'),
+            b @ b'a'..=b'f' => Ok(b - b'a' + 10),
+            b @ b'A'..=b'F' => Ok(b - b'A' + 10),
+            _ => Err(DecodeError::InvalidByte(index)),
+        };
+
+        let mut bytes = Self::with_capacity(hex.len() / 2);
+        for i in (0..hex.len()).step_by(2) {
+            bytes.push(digit(i)? << 4 | digit(i + 1)?);
+        }
+
+        Ok(bytes)
+    }
+
+    /// The contents as padded standard base64, stored inline if it fits in `M` bytes
+    pub fn to_base64<const M: usize>(&self) -> LocalStorageString<M> {
+        let mut encoded = LocalStorageString::with_capacity(self.len().div_ceil(3) * 4);
+
+        for chunk in self.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+            // a chunk of n bytes needs n + 1 characters, the rest is padding
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let sextet = (bits >> (18 - 6 * i)) & 0x3F;
+                    encoded.push(BASE64_ALPHABET[sextet as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+
+        encoded
+    }
+
+    /// Decode padded standard base64
+    pub fn from_base64(base64: &str) -> Result<Self, DecodeError> {
+        let base64 = base64.as_bytes();
+        if !base64.len().is_multiple_of(4) {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let padding = base64.iter().rev().take(2).filter(|&&b| b == b'=').count();
+        let data_len = base64.len() - padding;
+
+        let sextet = |index: usize| {
+            let byte = base64[index];
+            let position = BASE64_ALPHABET.iter().position(|&b| b == byte);
+            match position {
+                Some(value) if index < data_len => Ok(value as u32),
+                _ => Err(DecodeError::InvalidByte(index)),
+            }
+        };
+
+        let mut bytes = Self::with_capacity(base64.len() / 4 * 3 - padding);
+        for start in (0..base64.len()).step_by(4) {
+            let present = (data_len - start).min(4);
+
+            let mut bits = 0;
+            for i in 0..present {
+                bits |= sextet(start + i)? << (18 - 6 * i);
+            }
+
+            let group = bits.to_be_bytes();
+            bytes.extend_from_slice(&group[1..present]);
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex() {
+        let bytes: LocalStorageVec<u8, 4> = LocalStorageVec::from_hex("00fFa5").unwrap();
+        assert_eq!(&bytes[..], &[0x00, 0xFF, 0xA5]);
+
+        let hex: LocalStorageString<8> = bytes.to_hex();
+        assert_eq!(hex.as_str(), "00ffa5");
+
+        assert_eq!(
+            LocalStorageVec::<u8, 4>::from_hex("abc"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            LocalStorageVec::<u8, 4>::from_hex("0g"),
+            Err(DecodeError::InvalidByte(1))
+        );
+    }
+
+    #[test]
+    fn base64() {
+        for (input, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            let mut bytes: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+            bytes.extend_from_slice(input.as_bytes());
+
+            let to: LocalStorageString<8> = bytes.to_base64();
+            assert_eq!(to.as_str(), encoded);
+
+            let from = LocalStorageVec::<u8, 4>::from_base64(encoded).unwrap();
+            assert_eq!(&from[..], input.as_bytes());
+        }
+
+        assert_eq!(
+            LocalStorageVec::<u8, 4>::from_base64("Zg="),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            LocalStorageVec::<u8, 4>::from_base64("Z=g="),
+            Err(DecodeError::InvalidByte(1))
+        );
+        assert_eq!(
+            LocalStorageVec::<u8, 4>::from_base64("Zm9v!A=="),
+            Err(DecodeError::InvalidByte(4))
+        );
+    }
+}
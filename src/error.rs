@@ -0,0 +1,239 @@
+use std::fmt;
+use std::str::Utf8Error;
+
+use crate::LocalStorageVec;
+
+/// An element didn't fit in the available capacity. Holds the element, so it isn't lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    pub(crate) fn new(element: T) -> Self {
+        Self { element }
+    }
+
+    /// The element that didn't fit
+    pub fn into_inner(self) -> T {
+        self.element
+    }
+}
+
+/// An index was out of bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexError {
+    pub index: usize,
+    pub len: usize,
+}
+
+/// A range was out of bounds, or starts after it ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeError {
+    pub start: usize,
+    pub end: usize,
+    pub len: usize,
+}
+
+/// The error of `try_insert`. Holds the element, so it isn't lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryInsertError<T> {
+    /// the vector is at capacity
+    Capacity(CapacityError<T>),
+    /// the index is larger than the length
+    Index(IndexError, T),
+}
+
+impl<T> TryInsertError<T> {
+    /// The element that wasn't inserted
+    pub fn into_inner(self) -> T {
+        match self {
+            TryInsertError::Capacity(error) => error.into_inner(),
+            TryInsertError::Index(_, element) => element,
+        }
+    }
+}
+
+/// The error of `try_from_iter_inline`: the iterator yielded more than `N` elements. Holds
+/// everything that was taken from the iterator, and the iterator itself, so nothing is lost.
+#[derive(Debug, Clone)]
+pub struct FromIterError<T, I, const N: usize> {
+    /// the first `N` elements
+    pub collected: LocalStorageVec<T, N>,
+    /// the element that didn't fit
+    pub overflow: T,
+    /// the rest of the iterator
+    pub rest: I,
+}
+
+impl<T, I, const N: usize> FromIterError<T, I, N> {
+    /// How many elements were accepted before the capacity ran out, i.e. `N`
+    pub fn accepted(&self) -> usize {
+        self.collected.len()
+    }
+}
+
+/// Bytes that are not valid UTF-8. Holds the bytes, so they aren't lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromUtf8Error<const N: usize> {
+    bytes: LocalStorageVec<u8, N>,
+    error: Utf8Error,
+}
+
+impl<const N: usize> FromUtf8Error<N> {
+    pub(crate) fn new(bytes: LocalStorageVec<u8, N>, error: Utf8Error) -> Self {
+        Self { bytes, error }
+    }
+
+    /// The bytes that were being converted
+    pub fn into_bytes(self) -> LocalStorageVec<u8, N> {
+        self.bytes
+    }
+
+    /// Where the invalid UTF-8 is
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+/// The error of `try_reserve`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// the required capacity overflows `usize`
+    CapacityOverflow,
+    /// the heap buffer could not be allocated or grown
+    Heap(std::collections::TryReserveError),
+    /// the requested capacity is smaller than the length (`try_grow`)
+    BelowLength { capacity: usize, len: usize },
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for CapacityError<T> {}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let IndexError { index, len } = self;
+        write!(f, "index {index} out of bounds for length {len}")
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let RangeError { start, end, len } = self;
+        write!(f, "range {start}..{end} out of bounds for length {len}")
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+impl<T> fmt::Display for TryInsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryInsertError::Capacity(error) => error.fmt(f),
+            TryInsertError::Index(error, _) => error.fmt(f),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for TryInsertError<T> {}
+
+impl<T, I, const N: usize> fmt::Display for FromIterError<T, I, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "iterator yields more than {N} elements")
+    }
+}
+
+impl<T: fmt::Debug, I: fmt::Debug, const N: usize> std::error::Error for FromIterError<T, I, N> {}
+
+impl<const N: usize> fmt::Display for FromUtf8Error<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<const N: usize> std::error::Error for FromUtf8Error<N> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryReserveError::Heap(error) => error.fmt(f),
+            TryReserveError::BelowLength { capacity, len } => {
+                write!(f, "capacity (is {capacity}) should be >= len (is {len})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TryReserveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TryReserveError::CapacityOverflow => None,
+            TryReserveError::Heap(error) => Some(error),
+            TryReserveError::BelowLength { .. } => None,
+        }
+    }
+}
+
+impl From<std::collections::TryReserveError> for TryReserveError {
+    fn from(error: std::collections::TryReserveError) -> Self {
+        TryReserveError::Heap(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(CapacityError::new(1).to_string(), "insufficient capacity");
+
+        let error = IndexError { index: 3, len: 2 };
+        assert_eq!(error.to_string(), "index 3 out of bounds for length 2");
+        assert_eq!(
+            TryInsertError::Index(error, 'x').to_string(),
+            error.to_string()
+        );
+
+        let error = RangeError {
+            start: 1,
+            end: 4,
+            len: 3,
+        };
+        assert_eq!(error.to_string(), "range 1..4 out of bounds for length 3");
+
+        let error = LocalStorageVec::<u8, 1>::try_from_iter_inline([1, 2]).unwrap_err();
+        assert_eq!(error.to_string(), "iterator yields more than 1 elements");
+
+        assert_eq!(
+            TryReserveError::CapacityOverflow.to_string(),
+            "capacity overflow"
+        );
+    }
+
+    #[test]
+    fn composes_with_question_mark() {
+        fn fill(lsv: &mut LocalStorageVec<u8, 2>) -> Result<(), Box<dyn std::error::Error>> {
+            lsv.try_reserve(1)?;
+            lsv.try_push(1)?;
+            lsv.try_insert(5, 2)?;
+            Ok(())
+        }
+
+        let mut lsv = LocalStorageVec::new();
+        let error = fill(&mut lsv).unwrap_err();
+        assert_eq!(error.to_string(), "index 5 out of bounds for length 1");
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+use crate::LocalStorageVec;
+
+/// A `LocalStorageVec` that is indexed by a typed index `I` instead of `usize`, so indices into
+/// different vectors can't be mixed up.
+pub struct SmallIndexVec<I, T, const N: usize> {
+    vec: LocalStorageVec<T, N>,
+    // `fn(I) -> I`: doesn't own an `I`, so the auto traits and drop check only depend on `T`
+    _index: PhantomData<fn(I) -> I>,
+}
+
+impl<I, T: Default, const N: usize> SmallIndexVec<I, T, N> {
+    pub fn new() -> Self {
+        Self {
+            vec: LocalStorageVec::new(),
+            _index: PhantomData,
+        }
+    }
+}
+
+impl<I: From<usize> + Into<usize>, T: Default, const N: usize> SmallIndexVec<I, T, N> {
+    /// Appends `value`, returning its index
+    pub fn push(&mut self, value: T) -> I {
+        let index = I::from(self.vec.len());
+        self.vec.push(value);
+        index
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+}
+
+impl<I: From<usize> + Into<usize>, T, const N: usize> SmallIndexVec<I, T, N> {
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// The index the next pushed element gets
+    pub fn next_index(&self) -> I {
+        I::from(self.vec.len())
+    }
+
+    pub fn get(&self, index: I) -> Option<&T> {
+        self.vec.get(index.into())
+    }
+
+    pub fn get_mut(&mut self, index: I) -> Option<&mut T> {
+        self.vec.get_mut(index.into())
+    }
+
+    /// The valid indices, in order
+    pub fn indices(&self) -> impl DoubleEndedIterator<Item = I> + ExactSizeIterator {
+        (0..self.vec.len()).map(I::from)
+    }
+
+    /// The elements with their indices
+    pub fn iter_enumerated(&self) -> impl DoubleEndedIterator<Item = (I, &T)> + ExactSizeIterator {
+        self.vec.iter().enumerate().map(|(i, e)| (I::from(i), e))
+    }
+
+    /// The elements with their indices
+    pub fn iter_enumerated_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (I, &mut T)> + ExactSizeIterator {
+        self.vec
+            .iter_mut()
+            .enumerate()
+            .map(|(i, e)| (I::from(i), e))
+    }
+
+    /// The elements, without their indices
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// The underlying vector, indexed by `usize`
+    pub fn into_inner(self) -> LocalStorageVec<T, N> {
+        self.vec
+    }
+}
+
+impl<I, T: Default, const N: usize> Default for SmallIndexVec<I, T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, T: Clone, const N: usize> Clone for SmallIndexVec<I, T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+            _index: PhantomData,
+        }
+    }
+}
+
+impl<I, T: fmt::Debug, const N: usize> fmt::Debug for SmallIndexVec<I, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.vec.iter()).finish()
+    }
+}
+
+impl<I: Into<usize>, T, const N: usize> Index<I> for SmallIndexVec<I, T, N> {
+    type Output = T;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.vec[index.into()]
+    }
+}
+
+impl<I: Into<usize>, T, const N: usize> IndexMut<I> for SmallIndexVec<I, T, N> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut self.vec[index.into()]
+    }
+}
+
+impl<I, T, const N: usize> From<LocalStorageVec<T, N>> for SmallIndexVec<I, T, N> {
+    fn from(vec: LocalStorageVec<T, N>) -> Self {
+        Self {
+            vec,
+            _index: PhantomData,
+        }
+    }
+}
+
+impl<I, T: Default, const N: usize> Extend<T> for SmallIndexVec<I, T, N> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
+        self.vec.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct NodeId(u32);
+
+    impl From<usize> for NodeId {
+        fn from(index: usize) -> Self {
+            NodeId(index as u32)
+        }
+    }
+
+    impl From<NodeId> for usize {
+        fn from(id: NodeId) -> Self {
+            id.0 as usize
+        }
+    }
+
+    #[test]
+    fn typed_indices() {
+        let mut nodes: SmallIndexVec<NodeId, &str, 2> = SmallIndexVec::new();
+
+        let a = nodes.push("a");
+        let b = nodes.push("b");
+        assert_eq!(nodes.next_index(), NodeId(2));
+        let c = nodes.push("c");
+
+        assert_eq!((a, b, c), (NodeId(0), NodeId(1), NodeId(2)));
+        assert_eq!(nodes[b], "b");
+
+        nodes[c] = "C";
+        assert_eq!(nodes.get(NodeId(3)), None);
+
+        let pairs: Vec<_> = nodes.iter_enumerated().collect();
+        assert_eq!(pairs, [(a, &"a"), (b, &"b"), (c, &"C")]);
+        assert!(nodes.indices().rev().eq([c, b, a]));
+    }
+}
@@ -0,0 +1,363 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+use crate::LocalStorageVec;
+
+/// An in-memory reader/writer over an owned `LocalStorageVec<u8, N>`.
+///
+/// This is the equivalent of `std::io::Cursor<Vec<u8>>`: reads and writes happen at the current
+/// position, and writing past the end grows the vector (spilling to the heap when it no longer
+/// fits inline).
+#[derive(Debug, Clone, Default)]
+pub struct Cursor<const N: usize> {
+    inner: LocalStorageVec<u8, N>,
+    pos: u64,
+}
+
+impl<const N: usize> Cursor<N> {
+    /// A cursor over `inner`, positioned at the start
+    pub fn new(inner: LocalStorageVec<u8, N>) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    pub fn into_inner(self) -> LocalStorageVec<u8, N> {
+        self.inner
+    }
+
+    pub fn get_ref(&self) -> &LocalStorageVec<u8, N> {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut LocalStorageVec<u8, N> {
+        &mut self.inner
+    }
+
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// The bytes between the current position and the end (empty if the position is past the end)
+    fn remaining_slice(&self) -> &[u8] {
+        let start = self.pos.min(self.inner.len() as u64) as usize;
+        &self.inner[start..]
+    }
+}
+
+impl<const N: usize> LocalStorageVec<u8, N> {
+    /// Wrap this vector in a [`Cursor`], positioned at the start
+    pub fn into_cursor(self) -> Cursor<N> {
+        Cursor::new(self)
+    }
+}
+
+impl<const N: usize> Read for Cursor<N> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.remaining_slice();
+        let n = remaining.len().min(buf.len());
+
+        buf[..n].copy_from_slice(&remaining[..n]);
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+}
+
+/// The cursor is its own buffer: `fill_buf` returns the bytes after the current position
+impl<const N: usize> BufRead for Cursor<N> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.remaining_slice())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<const N: usize> Seek for Cursor<N> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match style {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::End(n) => (self.inner.len() as u64, n),
+            SeekFrom::Current(n) => (self.pos, n),
+        };
+
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                self.pos = n;
+                Ok(n)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl<const N: usize> Write for Cursor<N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let pos = usize::try_from(self.pos).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cursor position exceeds maximum possible vector length",
+            )
+        })?;
+
+        // like `std::io::Cursor`, writing past the end first fills the gap with zeroes
+        let len = self.inner.len();
+        if pos > len {
+            self.inner.extend(std::iter::repeat_n(0, pos - len));
+        }
+
+        let overlap = buf.len().min(self.inner.len() - pos);
+        self.inner[pos..pos + overlap].copy_from_slice(&buf[..overlap]);
+        self.inner.extend(buf[overlap..].iter().copied());
+
+        self.pos += buf.len() as u64;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> LocalStorageVec<u8, N> {
+    /// Perform a single `read` into the spare capacity, and grow the length by the number of
+    /// bytes read.
+    ///
+    /// Inline, the bytes are read straight into the unused tail of the buffer. On the heap the
+    /// spare capacity is uninitialized: with the `nightly` feature it is handed to
+    /// [`Read::read_buf`] as is, otherwise at most 8 KiB of it is zeroed and
+    /// passed to `reader`.
+    ///
+    /// A full vector first grows (spilling to the heap if it is inline), so `Ok(0)` always means
+    /// that the reader is at EOF.
+    pub fn read_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        if self.len() == self.capacity() {
+            self.reserve(READ_CHUNK.min(self.len().max(32)));
+        }
+
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let n = reader.read(&mut buf[*len..])?;
+                *len += n;
+                Ok(n)
+            }
+            LocalStorageVec::Heap(v) => read_into_spare(v, reader),
+        }
+    }
+
+    /// Read all bytes until EOF, spilling to the heap when the inline buffer fills up.
+    ///
+    /// Returns the number of bytes read. Like [`Read::read_to_end`], bytes read before an error
+    /// are kept.
+    pub fn read_to_end_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let start = self.len();
+
+        loop {
+            match self {
+                LocalStorageVec::Heap(v) => {
+                    reader.read_to_end(v)?;
+                    break;
+                }
+                LocalStorageVec::Stack { len, .. } if *len == N => self.reserve(N.max(1)),
+                LocalStorageVec::Stack { .. } => match self.read_from(reader) {
+                    Ok(0) => break,
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                },
+            }
+        }
+
+        Ok(self.len() - start)
+    }
+}
+
+/// The most bytes of spare heap capacity that `read_from` zeroes for one `read`
+const READ_CHUNK: usize = 8 * 1024;
+
+#[cfg(not(all(feature = "nightly", not(feature = "safe-only"))))]
+fn read_into_spare<R: Read + ?Sized>(v: &mut Vec<u8>, reader: &mut R) -> io::Result<usize> {
+    let len = v.len();
+    let n = (v.capacity() - len).min(READ_CHUNK);
+    v.resize(len + n, 0);
+
+    let result = reader.read(&mut v[len..]);
+    v.truncate(len + *result.as_ref().unwrap_or(&0));
+
+    result
+}
+
+#[cfg(all(feature = "nightly", not(feature = "safe-only")))]
+fn read_into_spare<R: Read + ?Sized>(v: &mut Vec<u8>, reader: &mut R) -> io::Result<usize> {
+    let len = v.len();
+
+    let mut buf = io::BorrowedBuf::from(v.spare_capacity_mut());
+    reader.read_buf(buf.unfilled())?;
+    let n = buf.len();
+
+    // SAFETY: `read_buf` initialized the first `n` bytes of the spare capacity
+    unsafe { v.set_len(len + n) };
+
+    Ok(n)
+}
+
+/// Writing to a `LocalStorageVec<u8, N>` appends to it, like `Vec<u8>`
+impl<const N: usize> Write for LocalStorageVec<u8, N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read_and_seek() {
+        let mut lsv: LocalStorageVec<u8, 8> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5]);
+
+        let mut cursor = lsv.into_cursor();
+        let mut buf = [0; 2];
+
+        assert_eq!(cursor.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [1, 2]);
+
+        assert_eq!(cursor.seek(SeekFrom::End(-1)).unwrap(), 4);
+        assert_eq!(cursor.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 5);
+        assert_eq!(cursor.read(&mut buf).unwrap(), 0);
+
+        assert!(cursor.seek(SeekFrom::Current(-6)).is_err());
+        assert_eq!(cursor.position(), 5);
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut lsv: LocalStorageVec<u8, 16> = LocalStorageVec::new();
+        lsv.extend(*b"one\ntwo\nthree");
+        let mut cursor = lsv.into_cursor();
+
+        let mut line = String::new();
+        cursor.read_line(&mut line).unwrap();
+        assert_eq!(line, "one\n");
+
+        let rest: Vec<String> = cursor.lines().map(Result::unwrap).collect();
+        assert_eq!(rest, ["two", "three"]);
+
+        let mut cursor = Cursor::new(LocalStorageVec::<u8, 4>::Heap(b"a,b".to_vec()));
+        let mut field = Vec::new();
+        cursor.read_until(b',', &mut field).unwrap();
+        assert_eq!(field, b"a,");
+        assert_eq!(cursor.fill_buf().unwrap(), b"b");
+
+        cursor.consume(1);
+        assert!(cursor.fill_buf().unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_overwrites_then_extends() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3]);
+
+        let mut cursor = Cursor::new(lsv);
+        cursor.set_position(2);
+        cursor.write_all(&[7, 8, 9]).unwrap();
+
+        let lsv = cursor.into_inner();
+        assert_eq!(&lsv[..], &[1, 2, 7, 8, 9]);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+    }
+
+    #[test]
+    fn read_from_fills_spare_capacity() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.push(1);
+
+        let mut reader: &[u8] = &[2, 3, 4, 5, 6];
+        assert_eq!(lsv.read_from(&mut reader).unwrap(), 3);
+        assert_eq!(&lsv[..], &[1, 2, 3, 4]);
+
+        // no spare capacity left inline, so the vector spills rather than returning `Ok(0)`
+        assert_eq!(lsv.read_from(&mut reader).unwrap(), 2);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5, 6]);
+
+        // now `Ok(0)` is EOF
+        assert_eq!(lsv.read_from(&mut reader).unwrap(), 0);
+
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::Heap(Vec::with_capacity(8));
+        let mut reader: &[u8] = &[7, 8];
+        assert_eq!(lsv.read_from(&mut reader).unwrap(), 2);
+        assert_eq!(&lsv[..], &[7, 8]);
+    }
+
+    #[test]
+    fn read_from_full_heap_vector_grows() {
+        // `vec!` allocates exactly the 3 bytes
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(vec![1, 2, 3]);
+        assert_eq!(lsv.capacity(), 3);
+
+        let mut reader: &[u8] = &[4];
+        assert_eq!(lsv.read_from(&mut reader).unwrap(), 1);
+        assert_eq!(&lsv[..], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn read_from_zeroes_at_most_a_chunk() {
+        struct Probe(usize);
+
+        impl Read for Probe {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.0 = buf.len();
+                Ok(0)
+            }
+        }
+
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(Vec::with_capacity(64 * 1024));
+        let mut probe = Probe(0);
+        lsv.read_from(&mut probe).unwrap();
+
+        #[cfg(not(all(feature = "nightly", not(feature = "safe-only"))))]
+        assert_eq!(probe.0, READ_CHUNK);
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    fn read_to_end_spills() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+
+        let mut reader: &[u8] = &[1, 2];
+        assert_eq!(lsv.read_to_end_from(&mut reader).unwrap(), 2);
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+
+        let mut reader: &[u8] = &[3, 4, 5, 6, 7];
+        assert_eq!(lsv.read_to_end_from(&mut reader).unwrap(), 5);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn write_past_end_zero_fills() {
+        let mut cursor: Cursor<8> = Cursor::default();
+        cursor.set_position(2);
+        cursor.write_all(&[1]).unwrap();
+
+        assert_eq!(&cursor.get_ref()[..], &[0, 0, 1]);
+    }
+}
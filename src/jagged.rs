@@ -0,0 +1,290 @@
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+
+use crate::LocalStorageVec;
+
+/// A list of rows of varying length, stored flat: all elements live in one `LocalStorageVec`,
+/// and a second one holds where each row ends. Both store up to `N` items inline.
+///
+/// Compared to `LocalStorageVec<LocalStorageVec<T, M>, N>` there is no per-row capacity that
+/// goes unused, and spilling causes two allocations in total rather than one per row. Rows can
+/// only be added at the end.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct LocalStorageJagged<T, const N: usize> {
+    values: LocalStorageVec<T, N>,
+    // invariant: non-decreasing, and the last end is `values.len()`
+    ends: LocalStorageVec<usize, N>,
+}
+
+/// The range of `values` that holds row `index`, which must exist
+fn row_range(ends: &[usize], index: usize) -> Range<usize> {
+    let start = match index {
+        0 => 0,
+        _ => ends[index - 1],
+    };
+
+    start..ends[index]
+}
+
+impl<T: Default, const N: usize> LocalStorageJagged<T, N> {
+    pub fn new() -> Self {
+        Self {
+            values: LocalStorageVec::new(),
+            ends: LocalStorageVec::new(),
+        }
+    }
+
+    /// Appends a row holding the elements of `row`
+    pub fn push_row<I: IntoIterator<Item = T>>(&mut self, row: I) {
+        self.values.extend(row);
+        self.ends.push(self.values.len());
+    }
+
+    /// Appends `value` to the last row. If there are no rows yet, a row is added first.
+    pub fn push_to_last_row(&mut self, value: T) {
+        self.values.push(value);
+
+        match self.ends.last_mut() {
+            Some(end) => *end += 1,
+            None => self.ends.push(1),
+        }
+    }
+
+    /// Removes the last row, and returns its elements
+    pub fn pop_row(&mut self) -> Option<LocalStorageVec<T, N>> {
+        self.ends.pop()?;
+        let start = self.ends.last().copied().unwrap_or(0);
+
+        let mut row = LocalStorageVec::new();
+        row.extend(self.values.drain(start..));
+        Some(row)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.ends.clear();
+    }
+}
+
+impl<T, const N: usize> LocalStorageJagged<T, N> {
+    /// The number of rows
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// The number of elements, summed over all rows
+    pub fn flat_len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The row at `index`.
+    ///
+    /// Panics if `index >= len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the index is out of bounds, use `get_row`")
+    )]
+    pub fn row(&self, index: usize) -> &[T] {
+        match self.get_row(index) {
+            Some(row) => row,
+            None => row_index_out_of_bounds(index, self.len()),
+        }
+    }
+
+    /// The row at `index`, or `None` if there are not that many rows
+    pub fn get_row(&self, index: usize) -> Option<&[T]> {
+        if index < self.len() {
+            Some(&self.values[row_range(&self.ends, index)])
+        } else {
+            None
+        }
+    }
+
+    /// The row at `index`, or `None` if there are not that many rows
+    pub fn get_row_mut(&mut self, index: usize) -> Option<&mut [T]> {
+        if index < self.len() {
+            Some(&mut self.values[row_range(&self.ends, index)])
+        } else {
+            None
+        }
+    }
+
+    /// All elements, row after row
+    pub fn as_flat_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// An iterator over the rows
+    pub fn rows(&self) -> Rows<'_, T> {
+        Rows {
+            values: &self.values,
+            ends: &self.ends,
+            front: 0,
+            back: self.ends.len(),
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn row_index_out_of_bounds(index: usize, len: usize) -> ! {
+    panic!("row index (is {index}) should be < len (is {len})");
+}
+
+impl<T, const N: usize> Index<usize> for LocalStorageJagged<T, N> {
+    type Output = [T];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get_row(index) {
+            Some(row) => row,
+            None => row_index_out_of_bounds(index, self.len()),
+        }
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for LocalStorageJagged<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let len = self.len();
+        match self.get_row_mut(index) {
+            Some(row) => row,
+            None => row_index_out_of_bounds(index, len),
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for LocalStorageJagged<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+impl<T: Default, R: IntoIterator<Item = T>, const N: usize> Extend<R> for LocalStorageJagged<T, N> {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, iter: I) {
+        for row in iter {
+            self.push_row(row);
+        }
+    }
+}
+
+impl<T: Default, R: IntoIterator<Item = T>, const N: usize> FromIterator<R>
+    for LocalStorageJagged<T, N>
+{
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let mut jagged = Self::new();
+        jagged.extend(iter);
+        jagged
+    }
+}
+
+/// Iterator over the rows of a [`LocalStorageJagged`]
+pub struct Rows<'a, T> {
+    values: &'a [T],
+    ends: &'a [usize],
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Rows<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        let row = &self.values[row_range(self.ends, self.front)];
+        self.front += 1;
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Rows<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(&self.values[row_range(self.ends, self.back)])
+    }
+}
+
+impl<T> ExactSizeIterator for Rows<'_, T> {}
+
+impl<'a, T, const N: usize> IntoIterator for &'a LocalStorageJagged<T, N> {
+    type Item = &'a [T];
+    type IntoIter = Rows<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_and_index_rows() {
+        let mut jagged: LocalStorageJagged<u32, 8> = LocalStorageJagged::new();
+
+        jagged.push_row([1, 2, 3]);
+        jagged.push_row([]);
+        jagged.push_row([4]);
+        jagged.push_to_last_row(5);
+
+        assert_eq!(jagged.len(), 3);
+        assert_eq!(jagged.flat_len(), 5);
+        assert_eq!(&jagged[0], [1, 2, 3]);
+        assert_eq!(jagged.row(1), []);
+        assert_eq!(jagged.get_row(2), Some(&[4, 5][..]));
+        assert_eq!(jagged.get_row(3), None);
+
+        jagged[0][1] = 20;
+        assert_eq!(jagged.as_flat_slice(), [1, 20, 3, 4, 5]);
+        assert_eq!(format!("{jagged:?}"), "[[1, 20, 3], [], [4, 5]]");
+    }
+
+    #[test]
+    fn rows_from_both_ends() {
+        let jagged: LocalStorageJagged<u8, 2> =
+            [vec![1], vec![2, 3], vec![], vec![4]].into_iter().collect();
+
+        let mut rows = jagged.rows();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows.next(), Some(&[1][..]));
+        assert_eq!(rows.next_back(), Some(&[4][..]));
+        assert_eq!(rows.next_back(), Some(&[][..]));
+        assert_eq!(rows.next(), Some(&[2, 3][..]));
+        assert_eq!(rows.next(), None);
+    }
+
+    #[test]
+    fn pop_row() {
+        let mut jagged: LocalStorageJagged<u8, 4> =
+            [vec![1, 2], vec![3, 4, 5]].into_iter().collect();
+
+        assert_eq!(jagged.pop_row().unwrap().as_slice(), [3, 4, 5]);
+        assert_eq!(jagged.pop_row().unwrap().as_slice(), [1, 2]);
+        assert_eq!(jagged.pop_row(), None);
+        assert!(jagged.is_empty());
+        assert_eq!(jagged.flat_len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "row index (is 1) should be < len (is 1)")]
+    fn index_out_of_bounds() {
+        let jagged: LocalStorageJagged<u8, 4> = [[1]].into_iter().collect();
+        let _ = &jagged[1];
+    }
+}
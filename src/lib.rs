@@ -1,4 +1,87 @@
-use std::ops::{Deref, DerefMut};
+// the deprecations only steer downstream code towards the checked API
+#![cfg_attr(feature = "deprecate-panicking", allow(deprecated))]
+#![cfg_attr(feature = "safe-only", forbid(unsafe_code))]
+#![cfg_attr(feature = "nightly", feature(min_specialization, trusted_len))]
+#![cfg_attr(
+    all(feature = "nightly", not(feature = "safe-only")),
+    feature(core_io_borrowed_buf, read_buf)
+)]
+
+use std::cmp::Ordering;
+#[cfg(not(feature = "safe-only"))]
+use std::mem::ManuallyDrop;
+use std::ops::{Add, AddAssign, Bound, Deref, DerefMut, Range, RangeBounds};
+#[cfg(not(feature = "safe-only"))]
+use std::ptr;
+
+mod aligned;
+#[cfg(not(feature = "safe-only"))]
+mod arena;
+mod binary_heap;
+mod bitset;
+#[cfg(not(feature = "safe-only"))]
+mod boxed;
+mod bytes;
+mod collect;
+mod collection;
+mod cow;
+pub mod deque;
+mod display;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod error;
+mod index_vec;
+mod io;
+mod jagged;
+pub mod map;
+mod memory;
+mod metrics;
+#[cfg(test)]
+mod model;
+mod scratch;
+pub mod set;
+mod shared;
+mod sorted;
+pub mod sorted_map;
+#[cfg(feature = "nightly")]
+mod spec_extend;
+mod spill;
+mod string;
+#[cfg(kani)]
+mod verification;
+
+pub use aligned::{Align16, Align32, Align64, AlignedLocalStorageVec};
+#[cfg(not(feature = "safe-only"))]
+pub use arena::LocalStorageArena;
+pub use binary_heap::LocalStorageBinaryHeap;
+pub use bitset::LocalStorageBitSet;
+#[cfg(not(feature = "safe-only"))]
+pub use boxed::LocalStorageBox;
+pub use collect::CollectLocalStorageVec;
+pub use collection::SmallCollection;
+pub use cow::LocalStorageCow;
+pub use deque::LocalStorageVecDeque;
+pub use display::DisplayJoin;
+#[cfg(feature = "encoding")]
+pub use encoding::DecodeError;
+pub use error::{
+    CapacityError, FromIterError, FromUtf8Error, IndexError, RangeError, TryInsertError,
+    TryReserveError,
+};
+pub use index_vec::SmallIndexVec;
+pub use io::Cursor;
+pub use jagged::LocalStorageJagged;
+pub use map::LocalStorageMap;
+pub use memory::MemoryUsage;
+#[cfg(feature = "metrics")]
+pub use metrics::{stats, Stats};
+pub use scratch::with_scratch;
+pub use set::LocalStorageSet;
+pub use shared::LocalStorageSharedVec;
+pub use sorted::SortedLocalStorageVec;
+pub use sorted_map::LocalStorageSortedMap;
+pub use spill::{set_spill_hook, SpillEvent};
+pub use string::LocalStorageString;
 
 // ------- STEP 1 -------
 
@@ -13,13 +96,20 @@ pub enum LocalStorageVec<T, const N: usize> {
 impl<T: Default, const N: usize> LocalStorageVec<T, N> {
     // hint: the Default instance on arrays
     pub fn new() -> Self {
-        todo!()
+        Self::Stack {
+            buf: std::array::from_fn(|_| T::default()),
+            len: 0,
+        }
     }
 
     // hint: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.with_capacity
     /// A `LocalStorageVec` with 0 elements, but which has space for `capacity` elements
     pub fn with_capacity(capacity: usize) -> Self {
-        todo!()
+        if capacity <= N {
+            Self::new()
+        } else {
+            Self::Heap(Vec::with_capacity(capacity))
+        }
     }
 }
 
@@ -31,17 +121,34 @@ impl<T: Default, const N: usize> Default for LocalStorageVec<T, N> {
 }
 
 impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// The number of elements that fit inline, i.e. `N`
+    pub const INLINE_CAPACITY: usize = N;
+
+    /// The number of elements that fit inline, i.e. `N`
+    pub const fn inline_capacity() -> usize {
+        N
+    }
+
     // hint: `match self { .. }`
+    #[inline]
     pub fn is_empty(&self) -> bool {
-        todo!()
+        self.len() == 0
     }
 
+    #[inline]
     pub fn len(&self) -> usize {
-        todo!()
+        match self {
+            Self::Stack { len, .. } => *len,
+            Self::Heap(v) => v.len(),
+        }
     }
 
+    #[inline]
     pub fn capacity(&self) -> usize {
-        todo!()
+        match self {
+            Self::Stack { .. } => N,
+            Self::Heap(v) => v.capacity(),
+        }
     }
 }
 
@@ -79,46 +186,85 @@ mod test2 {
 
     #[test]
     fn is_empty() {
-        todo!("add asserts for the vec and array case testing is_empty")
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 4],
+            len: 0,
+        };
+        assert!(lsv.is_empty());
+
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 4],
+            len: 2,
+        };
+        assert!(!lsv.is_empty());
+
+        let lsv: LocalStorageVec<u8, 12> = LocalStorageVec::Heap(vec![]);
+        assert!(lsv.is_empty());
+
+        let lsv: LocalStorageVec<u8, 12> = LocalStorageVec::Heap(vec![1]);
+        assert!(!lsv.is_empty());
+    }
+
+    #[test]
+    fn inline_capacity() {
+        type Small = LocalStorageVec<u8, 12>;
+
+        let buf = [0u8; Small::INLINE_CAPACITY];
+        assert_eq!(buf.len(), 12);
+        assert_eq!(Small::inline_capacity(), 12);
     }
 }
 
 // ------- STEP 3 -------
 
 impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    #[inline]
     pub fn push(&mut self, value: T) {
         match self {
             LocalStorageVec::Stack { buf, len } if *len < N => {
-                todo!()
+                buf[*len] = value;
+                *len += 1;
             }
-            LocalStorageVec::Stack { buf, len } => {
-                let mut v = Vec::with_capacity(*len + 1);
+            LocalStorageVec::Stack { .. } => self.push_spill(value),
+            LocalStorageVec::Heap(v) => {
+                let capacity = v.capacity();
+                v.push(value);
+                metrics::record_growth(capacity, v.capacity());
+            }
+        }
 
-                for e in buf.iter_mut() {
-                    // NOTE this trick: here we are able to take a value out of a `&mut T` reference!
-                    // (this works because `T` implements `Default`
-                    v.push(std::mem::take(e));
-                }
+        self.debug_check_invariants();
+    }
 
-                v.push(value);
+    // kept out of line, so the common case of `push` stays small enough to inline
+    #[cold]
+    #[inline(never)]
+    fn push_spill(&mut self, value: T) {
+        // moves the elements to the heap without calling `T::default`, which could panic halfway
+        // through and leave the vector filled with default values. This goes to `reserve_spill`
+        // directly: a (hand-built, invalid) inline vector with `len > N` would otherwise bounce
+        // between `push` and `reserve` forever
+        self.reserve_spill(1);
 
-                *self = LocalStorageVec::Heap(v);
-            }
-            LocalStorageVec::Heap(v) => {
-                todo!()
-            }
+        match self {
+            LocalStorageVec::Heap(v) => v.push(value),
+            LocalStorageVec::Stack { .. } => unreachable!("`reserve_spill` moves to the heap"),
         }
     }
 
+    #[inline]
     pub fn pop(&mut self) -> Option<T> {
-        match self {
+        let value = match self {
             LocalStorageVec::Stack { buf, len } if *len > 0 => {
-                // hint: use `std::mem::take` (see above)
-                todo!()
+                *len -= 1;
+                Some(std::mem::take(&mut buf[*len]))
             }
             Self::Stack { .. } => None,
             LocalStorageVec::Heap(v) => v.pop(),
-        }
+        };
+
+        self.debug_check_invariants();
+        value
     }
 }
 
@@ -150,16 +296,38 @@ mod test3 {
 
 impl<T: Default, const N: usize> Extend<T> for LocalStorageVec<T, N> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        #[cfg(feature = "nightly")]
+        spec_extend::SpecExtend::spec_extend(self, iter.into_iter());
+
+        #[cfg(not(feature = "nightly"))]
         for value in iter {
-            todo!()
+            self.push(value);
         }
     }
 }
 
+impl<'a, T: Copy + Default + 'a, const N: usize> Extend<&'a T> for LocalStorageVec<T, N> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        #[cfg(feature = "nightly")]
+        spec_extend::SpecExtend::spec_extend(self, iter.into_iter());
+
+        #[cfg(not(feature = "nightly"))]
+        self.extend(iter.into_iter().copied());
+    }
+}
+
 #[cfg(test)]
 mod test4 {
     use super::*;
 
+    #[test]
+    fn extend_from_references() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.extend(&[1, 2]);
+        lsv.extend([3, 4].iter());
+        assert_eq!(&lsv[..], &[1, 2, 3, 4]);
+    }
+
     #[test]
     fn dont_bend_extend() {
         let mut lsv = LocalStorageVec::Stack {
@@ -177,6 +345,20 @@ mod test4 {
         assert_eq!(lsv.len(), 5);
         assert!(matches!(lsv, LocalStorageVec::Heap(_)));
     }
+
+    #[test]
+    /// `Default` and `Extend` are all that `Iterator::unzip` needs
+    fn unzip() {
+        let pairs = [(1u8, 'a'), (2, 'b'), (3, 'c')];
+
+        let (numbers, letters): (LocalStorageVec<u8, 4>, LocalStorageVec<char, 2>) =
+            pairs.into_iter().unzip();
+
+        assert_eq!(&numbers[..], &[1, 2, 3]);
+        assert!(matches!(numbers, LocalStorageVec::Stack { .. }));
+        assert_eq!(&letters[..], &['a', 'b', 'c']);
+        assert!(matches!(letters, LocalStorageVec::Heap(_)));
+    }
 }
 
 // ------- STEP 5 -------
@@ -186,17 +368,123 @@ mod test4 {
 // - define an iterator type `IntoIter`, that implements Iterator.
 // - implement IntoIterator for LocalStorageVec
 
-pub enum IntoIter<T, const N: usize> {
-    Stack(std::iter::Take<std::array::IntoIter<T, N>>),
+pub struct IntoIter<T, const N: usize> {
+    repr: IntoIterRepr<T, N>,
+}
+
+enum IntoIterRepr<T, const N: usize> {
+    /// only the live elements are left, the unused slots are dropped up front
+    #[cfg(feature = "safe-only")]
+    Inline(std::array::IntoIter<T, N>),
+    /// invariant: exactly the elements in `start..end` are initialized. Only the live elements
+    /// of the vector are copied in, and they are read out one by one
+    #[cfg(not(feature = "safe-only"))]
+    Stack {
+        buf: [std::mem::MaybeUninit<T>; N],
+        start: usize,
+        end: usize,
+    },
     Heap(std::vec::IntoIter<T>),
 }
 
 impl<T, const N: usize> Iterator for IntoIter<T, N> {
     type Item = T;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        // hint: both the stack and heap variants contain an iterator already
-        todo!()
+        match &mut self.repr {
+            #[cfg(feature = "safe-only")]
+            IntoIterRepr::Inline(it) => it.next(),
+            #[cfg(not(feature = "safe-only"))]
+            IntoIterRepr::Stack { buf, start, end } if *start < *end => {
+                // SAFETY: the element at `start` is initialized, and is no longer part of the
+                // initialized range after this
+                let value = unsafe { buf[*start].assume_init_read() };
+                *start += 1;
+                Some(value)
+            }
+            #[cfg(not(feature = "safe-only"))]
+            IntoIterRepr::Stack { .. } => None,
+            IntoIterRepr::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match &self.repr {
+            #[cfg(feature = "safe-only")]
+            IntoIterRepr::Inline(it) => it.len(),
+            #[cfg(not(feature = "safe-only"))]
+            IntoIterRepr::Stack { start, end, .. } => end - start,
+            IntoIterRepr::Heap(it) => it.len(),
+        };
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.repr {
+            #[cfg(feature = "safe-only")]
+            IntoIterRepr::Inline(it) => it.next_back(),
+            #[cfg(not(feature = "safe-only"))]
+            IntoIterRepr::Stack { buf, start, end } if *start < *end => {
+                *end -= 1;
+                // SAFETY: the element at `end` was initialized, and is no longer part of the
+                // initialized range
+                Some(unsafe { buf[*end].assume_init_read() })
+            }
+            #[cfg(not(feature = "safe-only"))]
+            IntoIterRepr::Stack { .. } => None,
+            IntoIterRepr::Heap(it) => it.next_back(),
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+#[cfg(not(feature = "safe-only"))]
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        if let IntoIterRepr::Stack { buf, start, end } = &mut self.repr {
+            let live = ptr::slice_from_raw_parts_mut(
+                buf[*start..].as_mut_ptr().cast::<T>(),
+                *end - *start,
+            );
+
+            // SAFETY: the elements in `start..end` are initialized and not used after this
+            unsafe { ptr::drop_in_place(live) };
+        }
+    }
+}
+
+#[cfg(not(feature = "safe-only"))]
+impl<T, const N: usize> IntoIter<T, N> {
+    /// Copies only the live elements of an inline vector into the iterator
+    fn from_stack(lsv: LocalStorageVec<T, N>) -> Self {
+        let mut lsv = ManuallyDrop::new(lsv);
+        let LocalStorageVec::Stack { buf, len } = &mut *lsv else {
+            unreachable!("only called on inline vectors")
+        };
+        let len = *len;
+
+        let mut live = [const { std::mem::MaybeUninit::<T>::uninit() }; N];
+
+        // SAFETY: `len <= N` was checked by the caller. The live elements are moved into `live`
+        // and the unused slots (which the vector owns too) are dropped in place, so `lsv` no
+        // longer owns anything and is not dropped
+        unsafe {
+            ptr::copy_nonoverlapping(buf.as_ptr(), live.as_mut_ptr().cast::<T>(), len);
+            ptr::drop_in_place(&mut buf[len..]);
+        }
+
+        IntoIter {
+            repr: IntoIterRepr::Stack {
+                buf: live,
+                start: 0,
+                end: len,
+            },
+        }
     }
 }
 
@@ -206,8 +494,25 @@ impl<T, const N: usize> IntoIterator for LocalStorageVec<T, N> {
     type IntoIter = IntoIter<T, N>;
 
     fn into_iter(self) -> Self::IntoIter {
-        // hint: use the `it.take(N)` iterator method to only iterate over the first N elements
-        todo!();
+        // the iterator trusts `len`, so it must be checked in release builds too
+        self.check_invariants();
+
+        match self {
+            #[cfg(feature = "safe-only")]
+            LocalStorageVec::Stack { buf, len } => {
+                let mut it = buf.into_iter();
+                it.by_ref().rev().take(N - len).for_each(drop);
+
+                IntoIter {
+                    repr: IntoIterRepr::Inline(it),
+                }
+            }
+            #[cfg(not(feature = "safe-only"))]
+            stack @ LocalStorageVec::Stack { .. } => IntoIter::from_stack(stack),
+            LocalStorageVec::Heap(v) => IntoIter {
+                repr: IntoIterRepr::Heap(v.into_iter()),
+            },
+        }
     }
 }
 
@@ -228,21 +533,107 @@ mod test5 {
 
         assert_eq!(elements, vec![1, 2, 3, 4]);
     }
+
+    #[test]
+    fn into_iter_drops_each_element_once() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+
+        let mut lsv: LocalStorageVec<Option<Rc<()>>, 4> = LocalStorageVec::new();
+        lsv.extend((0..3).map(|_| Some(rc.clone())));
+        assert_eq!(Rc::strong_count(&rc), 4);
+
+        let mut iter = lsv.into_iter();
+        assert_eq!(iter.len(), 3);
+
+        drop(iter.next());
+        drop(iter.next_back());
+        assert_eq!(Rc::strong_count(&rc), 2);
+
+        drop(iter);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn into_iter_drops_unused_slots_once() {
+        use std::rc::Rc;
+
+        let (live, unused) = (Rc::new(()), Rc::new(()));
+        let lsv = LocalStorageVec::Stack {
+            buf: [live.clone(), unused.clone(), unused.clone()],
+            len: 1,
+        };
+
+        let mut iter = lsv.into_iter();
+        assert_eq!(Rc::strong_count(&unused), 1);
+
+        assert!(Rc::ptr_eq(&iter.next().unwrap(), &live));
+        assert!(iter.next().is_none());
+        drop(iter);
+        assert_eq!(Rc::strong_count(&live), 1);
+    }
+
+    #[test]
+    fn into_iter_double_ended() {
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 0xAA],
+            len: 3,
+        };
+        assert!(lsv.into_iter().rev().eq([3, 2, 1]));
+
+        let lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(vec![1, 2, 3]);
+        assert!(lsv.into_iter().rev().eq([3, 2, 1]));
+    }
 }
 
 // ------- STEP 6 -------
 
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// The elements as a slice (what `Deref` gives implicitly)
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            LocalStorageVec::Stack { buf, len } => &buf[..*len],
+            LocalStorageVec::Heap(v) => v,
+        }
+    }
+
+    /// The elements as a mutable slice (what `DerefMut` gives implicitly)
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match self {
+            LocalStorageVec::Stack { buf, len } => &mut buf[..*len],
+            LocalStorageVec::Heap(v) => v,
+        }
+    }
+}
+
 impl<T, const N: usize> Deref for LocalStorageVec<T, N> {
     type Target = [T];
 
+    #[inline]
     fn deref(&self) -> &Self::Target {
-        todo!()
+        self.as_slice()
     }
 }
 
 impl<T, const N: usize> DerefMut for LocalStorageVec<T, N> {
+    #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        todo!()
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for LocalStorageVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> AsMut<[T]> for LocalStorageVec<T, N> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
     }
 }
 
@@ -275,19 +666,197 @@ mod test6 {
             len: 2,
         };
 
-        assert_eq!(lsv[0], 1);
+        assert_eq!(lsv[0], 2);
+        assert_eq!(lsv[1], 1);
+    }
+
+    #[test]
+    fn test_as_slice() {
+        fn sum(values: impl AsRef<[u8]>) -> u8 {
+            values.as_ref().iter().sum()
+        }
+
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [2, 1u8, 0xAA, 0xAA],
+            len: 2,
+        };
+
+        lsv.as_mut_slice()[0] = 3;
+        assert_eq!(lsv.as_slice(), &[3, 1]);
+        assert_eq!(sum(&lsv), 4);
+        assert_eq!(sum(lsv), 4);
     }
 }
 
 // ------- STEP 7 -------
 
 impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Inserts `element` at `index`, shifting all elements after it to the right.
+    ///
+    /// Panics if `index > len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the index is out of bounds, use `try_insert`")
+    )]
     pub fn insert(&mut self, index: usize, element: T) {
-        todo!()
+        let len = self.len();
+        if index > len {
+            insert_index_out_of_bounds(index, len);
+        }
+
+        // push at the end (spilling if needed), then rotate it into place
+        self.push(element);
+        self[index..].rotate_right(1);
     }
 
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// Panics if `index >= len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the index is out of bounds, use `try_remove`")
+    )]
     pub fn remove(&mut self, index: usize) -> T {
-        todo!()
+        let len = self.len();
+        if index >= len {
+            remove_index_out_of_bounds(index, len);
+        }
+
+        self[index..].rotate_left(1);
+        self.pop().unwrap()
+    }
+
+    /// Shortens the vector to `new_len` elements, dropping the rest.
+    ///
+    /// Has no effect if `new_len >= len`.
+    pub fn truncate(&mut self, new_len: usize) {
+        match self {
+            LocalStorageVec::Stack { buf, len } if new_len < *len => {
+                // shorten first, so a panicking `drop` can't leave taken (default) values behind
+                let old_len = std::mem::replace(len, new_len);
+
+                for e in &mut buf[new_len..old_len] {
+                    drop(std::mem::take(e));
+                }
+            }
+            LocalStorageVec::Stack { .. } => {}
+            LocalStorageVec::Heap(v) => v.truncate(new_len),
+        }
+
+        self.debug_check_invariants();
+    }
+
+    /// Resizes to `new_len` elements, filling new slots with clones of `value`
+    pub fn resize(&mut self, new_len: usize, value: T)
+    where
+        T: Clone,
+    {
+        let len = self.len();
+
+        if new_len > len {
+            self.reserve(new_len - len);
+            self.extend(std::iter::repeat_n(value, new_len - len));
+        } else {
+            self.truncate(new_len);
+        }
+    }
+
+    /// Removes all elements
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|e| f(e));
+    }
+
+    /// Like [`LocalStorageVec::retain`], but `f` gets a mutable reference to each element
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        // kept elements are swapped to the front. Rejected elements end up in `kept..processed`,
+        // and are removed when the guard is dropped, also when `f` panics
+        struct Guard<'a, T: Default, const N: usize> {
+            vec: &'a mut LocalStorageVec<T, N>,
+            kept: usize,
+            processed: usize,
+        }
+
+        impl<T: Default, const N: usize> Drop for Guard<'_, T, N> {
+            fn drop(&mut self) {
+                self.vec.remove_range(self.kept..self.processed);
+            }
+        }
+
+        let len = self.len();
+        let mut guard = Guard {
+            vec: self,
+            kept: 0,
+            processed: 0,
+        };
+
+        while guard.processed < len {
+            let i = guard.processed;
+
+            if f(&mut guard.vec[i]) {
+                guard.vec.swap(guard.kept, i);
+                guard.kept += 1;
+            }
+
+            guard.processed += 1;
+        }
+    }
+
+    /// Like [`LocalStorageVec::retain`], but doesn't preserve the order of the kept elements.
+    ///
+    /// A rejected element is swapped with the last unprocessed one instead of shifting the
+    /// elements after it.
+    pub fn retain_unordered<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        // rejected elements are swapped to `end..`, and are removed when the guard is dropped,
+        // also when `f` panics
+        struct Guard<'a, T: Default, const N: usize> {
+            vec: &'a mut LocalStorageVec<T, N>,
+            end: usize,
+        }
+
+        impl<T: Default, const N: usize> Drop for Guard<'_, T, N> {
+            fn drop(&mut self) {
+                self.vec.truncate(self.end);
+            }
+        }
+
+        let end = self.len();
+        let mut guard = Guard { vec: self, end };
+
+        let mut i = 0;
+        while i < guard.end {
+            if f(&guard.vec[i]) {
+                i += 1;
+            } else {
+                guard.end -= 1;
+                guard.vec.swap(i, guard.end);
+            }
+        }
+    }
+
+    /// Clones and appends all elements of `other`
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(other.len());
+
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                // grow the length one element at a time, so a panicking `clone` keeps the prefix
+                for (slot, e) in buf[*len..].iter_mut().zip(other) {
+                    *slot = e.clone();
+                    *len += 1;
+                }
+            }
+            LocalStorageVec::Heap(v) => v.extend_from_slice(other),
+        }
+
+        self.debug_check_invariants();
     }
 }
 
@@ -295,5 +864,2538 @@ impl<T: Default, const N: usize> LocalStorageVec<T, N> {
 mod test7 {
     use super::*;
 
-    // write your own :)
+    #[test]
+    fn insert_remove_stack() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1u8, 3, 0xAA, 0xAA],
+            len: 2,
+        };
+
+        lsv.insert(1, 2);
+        lsv.insert(3, 4);
+        assert_eq!(&lsv[..], &[1, 2, 3, 4]);
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+
+        assert_eq!(lsv.remove(0), 1);
+        assert_eq!(lsv.remove(2), 4);
+        assert_eq!(&lsv[..], &[2, 3]);
+    }
+
+    #[test]
+    fn insert_spills() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 4, 5],
+            len: 4,
+        };
+
+        lsv.insert(2, 3);
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5]);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+    }
+
+    #[test]
+    fn truncate_and_extend_from_slice() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+
+        lsv.extend_from_slice(&[1, 2, 3]);
+        lsv.truncate(1);
+        assert_eq!(&lsv[..], &[1]);
+
+        lsv.extend_from_slice(&[4, 5, 6, 7]);
+        assert_eq!(&lsv[..], &[1, 4, 5, 6, 7]);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+
+        lsv.clear();
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    fn retain() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4]);
+
+        lsv.retain(|x| x % 2 == 0);
+        assert_eq!(&lsv[..], &[2, 4]);
+
+        lsv.extend([5, 6, 7]);
+        lsv.retain_mut(|x| {
+            *x *= 10;
+            *x > 20
+        });
+        assert_eq!(&lsv[..], &[40, 50, 60, 70]);
+    }
+
+    #[test]
+    fn retain_unordered() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5, 6]);
+
+        lsv.retain_unordered(|x| x % 3 != 0);
+        assert_eq!(&lsv[..], &[1, 2, 5, 4]);
+
+        lsv.retain_unordered(|_| false);
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.remove(0);
+    }
+}
+
+// ------- INVARIANTS -------
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Panics if the vector is in an invalid state, which can only happen when a `Stack` variant
+    /// is constructed by hand with `len > N`.
+    ///
+    /// The core mutating methods (`push`, `pop`, `truncate`, ..) call this in debug builds.
+    pub fn check_invariants(&self) {
+        if let LocalStorageVec::Stack { len, .. } = self {
+            if *len > N {
+                inline_length_out_of_bounds(*len, N);
+            }
+        }
+    }
+
+    #[inline]
+    fn debug_check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            self.check_invariants();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_invariants {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.check_invariants();
+
+        lsv.extend([1, 2, 3]);
+        lsv.check_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "inline length (is 5) should be <= N (is 4)")]
+    fn hand_constructed() {
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 4],
+            len: 5,
+        };
+
+        lsv.check_invariants();
+    }
+
+    // not gated on `debug_assertions`: these used to loop forever (push) or read out of bounds
+    // (spill, into_iter) in release builds
+    #[test]
+    #[should_panic(expected = "inline length (is 3) should be <= N (is 2)")]
+    fn push_past_invalid_length() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2],
+            len: 3,
+        };
+
+        lsv.push(9);
+    }
+
+    #[test]
+    #[should_panic(expected = "inline length (is 3) should be <= N (is 2)")]
+    fn reserve_past_invalid_length() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2],
+            len: 3,
+        };
+
+        lsv.reserve(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "inline length (is 3) should be <= N (is 2)")]
+    fn into_iter_past_invalid_length() {
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2],
+            len: 3,
+        };
+
+        let _ = lsv.into_iter();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "inline length")]
+    fn checked_by_mutating_methods() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 4],
+            len: 5,
+        };
+
+        lsv.push(5);
+    }
+}
+
+// ------- DROP CHECK -------
+
+// `LocalStorageVec` has no `Drop` impl of its own: dropping it drops the array or `Vec` inside,
+// both of which let the compiler know that dropping an element of type `&'a T` never uses the
+// reference. So, like `Vec`, it may hold references that dangle by the time it is dropped.
+
+#[cfg(test)]
+mod test_dropck {
+    use super::*;
+
+    #[test]
+    fn contents_may_dangle() {
+        let mut inline: LocalStorageVec<&str, 2> = LocalStorageVec::new();
+        let mut heap: LocalStorageVec<&str, 2> = LocalStorageVec::new();
+
+        // dropped before the vectors that borrow it, which is only allowed because dropping them
+        // doesn't read the references
+        let s = String::from("a");
+
+        inline.push(&s);
+        heap.extend([&s[..], &s, &s]);
+
+        assert!(matches!(heap, LocalStorageVec::Heap(_)));
+        assert_eq!(inline.len() + heap.len(), 4);
+    }
+}
+
+// ------- CAPACITY -------
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Reserve capacity for at least `additional` more elements.
+    ///
+    /// If the elements no longer fit inline, the contents are moved to the heap.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        match self {
+            LocalStorageVec::Stack { len, .. } => match N.checked_sub(*len) {
+                Some(spare) if additional <= spare => {}
+                Some(_) => self.reserve_spill(additional),
+                None => inline_length_out_of_bounds(*len, N),
+            },
+            LocalStorageVec::Heap(v) => {
+                let capacity = v.capacity();
+                v.reserve(additional);
+                metrics::record_growth(capacity, v.capacity());
+            }
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn reserve_spill(&mut self, additional: usize) {
+        let capacity = self
+            .len()
+            .checked_add(additional)
+            .unwrap_or_else(|| capacity_overflow());
+        self.spill(Vec::with_capacity(capacity));
+    }
+
+    /// Like [`LocalStorageVec::reserve`], but returns an error instead of panicking (or aborting)
+    /// when the capacity overflows or the allocation fails
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        match self {
+            LocalStorageVec::Stack { len, .. } if *len > N => inline_length_out_of_bounds(*len, N),
+            LocalStorageVec::Stack { len, .. } if additional <= N - *len => Ok(()),
+            LocalStorageVec::Stack { len, .. } => {
+                let capacity = len
+                    .checked_add(additional)
+                    .ok_or(TryReserveError::CapacityOverflow)?;
+
+                let mut v = Vec::new();
+                v.try_reserve_exact(capacity)?;
+                self.spill(v);
+
+                Ok(())
+            }
+            LocalStorageVec::Heap(v) => {
+                let capacity = v.capacity();
+                v.try_reserve(additional)?;
+                metrics::record_growth(capacity, v.capacity());
+                Ok(())
+            }
+        }
+    }
+
+    /// Move the inline elements into `v`, which must have enough capacity for them
+    fn spill(&mut self, mut v: Vec<T>) {
+        // checked in release builds too: moving `len > N` elements would read past the buffer
+        self.check_invariants();
+
+        // take ownership of the array so elements can be moved out without `T: Default`
+        if let LocalStorageVec::Stack { buf, len } =
+            std::mem::replace(self, LocalStorageVec::Heap(Vec::new()))
+        {
+            let unused = move_inline(buf, len, &mut v);
+            *self = LocalStorageVec::Heap(v);
+
+            // the unused slots are dropped last, so if that panics the vector is intact
+            drop(unused);
+
+            record_spill::<T>(len, N, self.capacity());
+        }
+    }
+
+    /// How many more elements fit before the vector spills (inline) or reallocates (heap)
+    pub fn remaining_capacity(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    /// Whether the next push spills (inline) or reallocates (heap)
+    pub fn is_full(&self) -> bool {
+        self.remaining_capacity() == 0
+    }
+
+    /// Take the contents, leaving an empty vector behind.
+    ///
+    /// Unlike `std::mem::take` this doesn't need `T: Default`. The empty vector that is left
+    /// behind is an (unallocated) heap vector, because an inline buffer can't be created without
+    /// values to fill it.
+    pub fn take(&mut self) -> Self {
+        std::mem::replace(self, LocalStorageVec::Heap(Vec::new()))
+    }
+}
+
+/// Move the first `len` elements of an inline buffer to the end of `v`, without `T: Default`.
+///
+/// The unused slots are returned instead of dropped: the caller installs `v` first, so that a
+/// panicking `drop` of an unused slot can't take the moved elements with it.
+pub(crate) fn move_inline<T, const N: usize>(
+    buf: [T; N],
+    len: usize,
+    v: &mut Vec<T>,
+) -> UnusedSlots<T, N> {
+    assert!(len <= N);
+
+    #[cfg(feature = "safe-only")]
+    {
+        let mut values = buf.into_iter();
+        v.extend(values.by_ref().take(len));
+        values
+    }
+
+    #[cfg(not(feature = "safe-only"))]
+    {
+        let buf = ManuallyDrop::new(buf);
+
+        // SAFETY: `len <= N`, the live elements are moved out once, and the array is not dropped
+        // as a whole
+        unsafe { move_to_heap(buf.as_ptr(), len, v) };
+
+        UnusedSlots { buf, start: len }
+    }
+}
+
+/// The slots of an inline buffer after its elements were moved out, dropped when this is
+#[cfg(feature = "safe-only")]
+pub(crate) type UnusedSlots<T, const N: usize> = std::array::IntoIter<T, N>;
+
+/// The slots of an inline buffer after its elements were moved out, dropped when this is
+#[cfg(not(feature = "safe-only"))]
+pub(crate) struct UnusedSlots<T, const N: usize> {
+    buf: ManuallyDrop<[T; N]>,
+    /// the slots before `start` were moved out
+    start: usize,
+}
+
+#[cfg(not(feature = "safe-only"))]
+impl<T, const N: usize> Drop for UnusedSlots<T, N> {
+    fn drop(&mut self) {
+        // SAFETY: the slots from `start` on were not moved out, and are dropped only here
+        unsafe { ptr::drop_in_place(&mut self.buf[self.start..]) };
+    }
+}
+
+/// Update the metrics and call the spill hook for a spill of `len` elements of type `T`
+pub(crate) fn record_spill<T>(len: usize, inline_capacity: usize, heap_capacity: usize) {
+    metrics::record_spill();
+    spill::report(spill::SpillEvent {
+        len,
+        element_size: std::mem::size_of::<T>(),
+        inline_capacity,
+        heap_capacity,
+    });
+}
+
+#[cfg(test)]
+mod test_capacity {
+    use super::*;
+
+    #[test]
+    fn reserve_inline() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 0xAA, 0xAA],
+            len: 2,
+        };
+
+        lsv.reserve(2);
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+
+        lsv.reserve(3);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert!(lsv.capacity() >= 5);
+        assert_eq!(&lsv[..], &[1, 2]);
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.push(1);
+
+        assert_eq!(lsv.try_reserve(1), Ok(()));
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+
+        assert_eq!(
+            lsv.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert!(matches!(
+            lsv.try_reserve(usize::MAX - 1),
+            Err(TryReserveError::Heap(_))
+        ));
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+
+        assert_eq!(lsv.try_reserve(4), Ok(()));
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(&lsv[..], &[1]);
+        assert!(lsv.try_reserve(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn remaining_capacity() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        assert_eq!(lsv.remaining_capacity(), 2);
+
+        lsv.extend([1, 2]);
+        assert!(lsv.is_full());
+
+        lsv.push(3);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(lsv.remaining_capacity(), lsv.capacity() - 3);
+    }
+
+    #[test]
+    fn take() {
+        struct NoDefault(u8);
+
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [NoDefault(1), NoDefault(2)],
+            len: 1,
+        };
+
+        let taken = lsv.take();
+        assert!(matches!(taken, LocalStorageVec::Stack { len: 1, .. }));
+        assert_eq!(taken[0].0, 1);
+
+        assert!(lsv.is_empty());
+        assert_eq!(lsv.capacity(), 0);
+    }
+}
+
+// ------- FALLIBLE EXTEND -------
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Extend with the `Ok` values of `iter`, stopping at the first `Err`.
+    ///
+    /// Elements pushed before the error stay in the vector.
+    pub fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        for value in iter {
+            self.push(value?);
+        }
+
+        Ok(())
+    }
+
+    /// Collect the `Ok` values of `iter`, or return the first `Err`
+    pub fn try_from_iter<E, I>(iter: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        let mut lsv = Self::new();
+        lsv.try_extend(iter)?;
+
+        Ok(lsv)
+    }
+
+    /// Collect `iter` into the inline buffer, without ever spilling to the heap.
+    ///
+    /// If the iterator yields more than `N` elements, the error holds the first `N`, the element
+    /// that didn't fit, and the rest of the iterator.
+    pub fn try_from_iter_inline<I>(iter: I) -> Result<Self, FromIterError<T, I::IntoIter, N>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut lsv = Self::new();
+
+        if let LocalStorageVec::Stack { buf, len } = &mut lsv {
+            for slot in buf.iter_mut() {
+                match iter.next() {
+                    Some(value) => *slot = value,
+                    None => return Ok(lsv),
+                }
+
+                *len += 1;
+            }
+        }
+
+        match iter.next() {
+            None => Ok(lsv),
+            Some(overflow) => Err(FromIterError {
+                collected: lsv,
+                overflow,
+                rest: iter,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_try_extend {
+    use super::*;
+
+    #[test]
+    fn stops_at_first_error() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+
+        let result = lsv.try_extend([Ok(1), Ok(2), Err("bad"), Ok(3)]);
+
+        assert_eq!(result, Err("bad"));
+        assert_eq!(&lsv[..], &[1, 2]);
+    }
+
+    #[test]
+    fn try_from_iter_inline() {
+        let lsv = LocalStorageVec::<u8, 4>::try_from_iter_inline([1, 2, 3]).unwrap();
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+
+        let lsv = LocalStorageVec::<u8, 2>::try_from_iter_inline([1, 2]).unwrap();
+        assert_eq!(&lsv[..], &[1, 2]);
+
+        let error = LocalStorageVec::<u8, 2>::try_from_iter_inline([1, 2, 3, 4]).unwrap_err();
+        assert_eq!(error.accepted(), 2);
+        assert_eq!(&error.collected[..], &[1, 2]);
+        assert_eq!(error.overflow, 3);
+        assert!(error.rest.eq([4]));
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let lsv = LocalStorageVec::<u8, 4>::try_from_iter("1 2 3".split(' ').map(str::parse));
+        assert_eq!(&lsv.unwrap()[..], &[1, 2, 3]);
+
+        let lsv = LocalStorageVec::<u8, 4>::try_from_iter("1 x 3".split(' ').map(str::parse));
+        assert!(lsv.is_err());
+    }
+}
+
+// ------- MAP -------
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Apply `f` to every element, keeping the storage mode: inline vectors stay inline, and the
+    /// heap buffer is reused when `U` has the same layout as `T`.
+    pub fn map<U: Default, F: FnMut(T) -> U>(self, f: F) -> LocalStorageVec<U, N> {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let mut values = buf.into_iter().take(len).map(f);
+                let buf = std::array::from_fn(|_| values.next().unwrap_or_default());
+
+                LocalStorageVec::Stack { buf, len }
+            }
+            LocalStorageVec::Heap(v) => LocalStorageVec::Heap(v.into_iter().map(f).collect()),
+        }
+    }
+
+    /// Like [`LocalStorageVec::map`], but stops at the first error. The elements converted so
+    /// far and the remaining unconverted elements are dropped.
+    pub fn try_map<U: Default, E, F: FnMut(T) -> Result<U, E>>(
+        self,
+        mut f: F,
+    ) -> Result<LocalStorageVec<U, N>, E> {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let mut values = buf.into_iter().take(len);
+                let mut error = None;
+
+                let buf = std::array::from_fn(|_| {
+                    if error.is_some() {
+                        return U::default();
+                    }
+
+                    match values.next().map(&mut f) {
+                        Some(Ok(value)) => value,
+                        Some(Err(e)) => {
+                            error = Some(e);
+                            U::default()
+                        }
+                        None => U::default(),
+                    }
+                });
+
+                match error {
+                    Some(e) => Err(e),
+                    None => Ok(LocalStorageVec::Stack { buf, len }),
+                }
+            }
+            LocalStorageVec::Heap(v) => v
+                .into_iter()
+                .map(f)
+                .collect::<Result<_, _>>()
+                .map(LocalStorageVec::Heap),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_map {
+    use super::*;
+
+    #[test]
+    fn map_keeps_storage_mode() {
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 0xAA],
+            len: 3,
+        };
+
+        let mut calls = 0;
+        let mapped = lsv.map(|x| {
+            calls += 1;
+            u32::from(x) * 1000
+        });
+
+        // `f` is not called on the unused slots
+        assert_eq!(calls, 3);
+        assert!(matches!(mapped, LocalStorageVec::Stack { .. }));
+        assert_eq!(&mapped[..], &[1000, 2000, 3000]);
+
+        let lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(vec![1, 2, 3]);
+        let mapped = lsv.map(|x| x.to_string());
+        assert!(matches!(mapped, LocalStorageVec::Heap(_)));
+        assert_eq!(&mapped[..], &["1", "2", "3"]);
+    }
+
+    #[test]
+    fn try_map() {
+        let lsv = LocalStorageVec::<&str, 4>::Stack {
+            buf: ["1", "2", "3", ""],
+            len: 3,
+        };
+        let parsed = lsv.try_map(str::parse::<u8>);
+        assert_eq!(&parsed.unwrap()[..], &[1, 2, 3]);
+
+        let lsv: LocalStorageVec<&str, 2> = LocalStorageVec::Heap(vec!["1", "x", "3"]);
+        assert!(lsv.try_map(str::parse::<u8>).is_err());
+    }
+
+    #[test]
+    fn try_map_drops_everything_on_error() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+
+        let mut lsv: LocalStorageVec<Option<Rc<()>>, 4> = LocalStorageVec::new();
+        lsv.extend((0..3).map(|_| Some(rc.clone())));
+        assert_eq!(Rc::strong_count(&rc), 4);
+
+        let mut seen = 0;
+        let result = lsv.try_map(|x| {
+            seen += 1;
+            if seen == 2 {
+                Err(())
+            } else {
+                Ok(x)
+            }
+        });
+
+        assert!(result.is_err());
+        assert_eq!(seen, 2);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
+
+// ------- FLATTEN -------
+
+impl<T: Default, const M: usize, const N: usize> LocalStorageVec<[T; M], N> {
+    /// Flatten a vector of arrays into a vector of their elements.
+    ///
+    /// The inline capacity `K` of the result is chosen by the caller (`N * M` can't be written on
+    /// stable Rust). A heap buffer is reused as-is; inline elements are moved into inline storage
+    /// if they fit in `K`.
+    pub fn into_flattened<const K: usize>(self) -> LocalStorageVec<T, K> {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let capacity = len.checked_mul(M).unwrap_or_else(|| capacity_overflow());
+
+                let mut flat = LocalStorageVec::with_capacity(capacity);
+                flat.extend(buf.into_iter().take(len).flatten());
+                flat
+            }
+            LocalStorageVec::Heap(v) => LocalStorageVec::Heap(v.into_flattened()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_flatten {
+    use super::*;
+
+    #[test]
+    fn into_flattened() {
+        let lsv = LocalStorageVec::Stack {
+            buf: [[1u8, 2], [3, 4], [0xAA, 0xAA]],
+            len: 2,
+        };
+
+        let flat: LocalStorageVec<u8, 4> = lsv.clone().into_flattened();
+        assert!(matches!(flat, LocalStorageVec::Stack { .. }));
+        assert_eq!(&flat[..], &[1, 2, 3, 4]);
+
+        let flat: LocalStorageVec<u8, 3> = lsv.into_flattened();
+        assert!(matches!(flat, LocalStorageVec::Heap(_)));
+        assert_eq!(&flat[..], &[1, 2, 3, 4]);
+
+        let lsv: LocalStorageVec<[u8; 2], 1> = LocalStorageVec::Heap(vec![[1, 2], [3, 4]]);
+        let ptr = lsv.as_ptr().cast::<u8>();
+        let flat: LocalStorageVec<u8, 8> = lsv.into_flattened();
+        assert_eq!(flat.as_ptr(), ptr);
+        assert_eq!(&flat[..], &[1, 2, 3, 4]);
+    }
+}
+
+// ------- CONCAT -------
+
+impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
+    /// Concatenate the contents of `parts`, allocating (if at all) once for the total length
+    pub fn concat<const M: usize>(parts: &[LocalStorageVec<T, M>]) -> Self {
+        let total = parts.iter().map(|part| part.len()).sum();
+
+        let mut lsv = Self::with_capacity(total);
+        for part in parts {
+            lsv.extend_from_slice(part);
+        }
+
+        lsv
+    }
+
+    /// The contents of `self`, repeated `n` times. The result is inline if it fits.
+    pub fn repeat(&self, n: usize) -> Self {
+        let total = self
+            .len()
+            .checked_mul(n)
+            .unwrap_or_else(|| capacity_overflow());
+
+        let mut lsv = Self::with_capacity(total);
+        for _ in 0..n {
+            lsv.extend_from_slice(self);
+        }
+
+        lsv
+    }
+}
+
+/// Appends the elements of each inner vector
+impl<T: Default, const N: usize, const M: usize> Extend<LocalStorageVec<T, M>>
+    for LocalStorageVec<T, N>
+{
+    fn extend<I: IntoIterator<Item = LocalStorageVec<T, M>>>(&mut self, iter: I) {
+        // gather the parts first, so (like `concat`) there is a single reservation for the total.
+        // A `Vec` of parts only holds the live parts, unlike an inline buffer of them
+        let parts: Vec<LocalStorageVec<T, M>> = iter.into_iter().collect();
+
+        let total = parts
+            .iter()
+            .try_fold(0usize, |total, part| total.checked_add(part.len()))
+            .unwrap_or_else(|| capacity_overflow());
+        self.reserve(total);
+
+        for part in parts {
+            self.extend(part);
+        }
+    }
+}
+
+/// Appends a clone of each element of the slice
+impl<T: Default + Clone, const N: usize> AddAssign<&[T]> for LocalStorageVec<T, N> {
+    fn add_assign(&mut self, rhs: &[T]) {
+        self.extend_from_slice(rhs);
+    }
+}
+
+/// Appends the elements of the other vector
+impl<T: Default, const N: usize, const M: usize> AddAssign<LocalStorageVec<T, M>>
+    for LocalStorageVec<T, N>
+{
+    fn add_assign(&mut self, rhs: LocalStorageVec<T, M>) {
+        self.reserve(rhs.len());
+        self.extend(rhs);
+    }
+}
+
+impl<T: Default + Clone, const N: usize> Add<&[T]> for LocalStorageVec<T, N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: &[T]) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<T: Default, const N: usize, const M: usize> Add<LocalStorageVec<T, M>>
+    for LocalStorageVec<T, N>
+{
+    type Output = Self;
+
+    fn add(mut self, rhs: LocalStorageVec<T, M>) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test_concat {
+    use super::*;
+
+    #[test]
+    fn concat() {
+        let parts: [LocalStorageVec<u8, 2>; 3] = [
+            LocalStorageVec::Stack {
+                buf: [1, 2],
+                len: 2,
+            },
+            LocalStorageVec::new(),
+            LocalStorageVec::Heap(vec![3, 4, 5]),
+        ];
+
+        let flat: LocalStorageVec<u8, 8> = LocalStorageVec::concat(&parts);
+        assert!(matches!(flat, LocalStorageVec::Stack { .. }));
+        assert_eq!(&flat[..], &[1, 2, 3, 4, 5]);
+
+        let flat: LocalStorageVec<u8, 4> = LocalStorageVec::concat(&parts);
+        assert!(matches!(flat, LocalStorageVec::Heap(_)));
+        assert_eq!(flat.capacity(), 5);
+    }
+
+    #[test]
+    fn extend_with_parts_reserves_once() {
+        let parts: [LocalStorageVec<u64, 2>; 3] = [
+            LocalStorageVec::from([1, 2]),
+            LocalStorageVec::Heap(vec![3, 4, 5]),
+            LocalStorageVec::from([6]),
+        ];
+
+        // reserving per part would grow the buffer to 4, then double it to 8
+        let mut lsv: LocalStorageVec<u64, 1> = LocalStorageVec::Heap(Vec::new());
+        lsv.extend(parts);
+
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(lsv.capacity(), 6);
+    }
+
+    #[test]
+    fn extend_with_parts_does_not_call_default() {
+        thread_local! {
+            static DEFAULTS_ALLOWED: std::cell::Cell<bool> = const { std::cell::Cell::new(true) };
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Fussy(u8);
+
+        impl Default for Fussy {
+            fn default() -> Self {
+                assert!(DEFAULTS_ALLOWED.get(), "default called");
+                Fussy(0)
+            }
+        }
+
+        let part: LocalStorageVec<Fussy, 16> = LocalStorageVec::from([Fussy(1), Fussy(2)]);
+        let mut lsv: LocalStorageVec<Fussy, 1> = LocalStorageVec::Heap(Vec::new());
+        DEFAULTS_ALLOWED.set(false);
+
+        lsv.extend([part]);
+        assert_eq!(&lsv[..], &[Fussy(1), Fussy(2)]);
+    }
+
+    #[test]
+    fn repeat() {
+        let lsv = LocalStorageVec::Stack {
+            buf: [1u8, 2, 0, 0, 0, 0],
+            len: 2,
+        };
+
+        let repeated = lsv.repeat(3);
+        assert!(matches!(repeated, LocalStorageVec::Stack { .. }));
+        assert_eq!(&repeated[..], &[1, 2, 1, 2, 1, 2]);
+
+        let repeated = lsv.repeat(4);
+        assert!(matches!(repeated, LocalStorageVec::Heap(_)));
+        assert_eq!(repeated.len(), 8);
+
+        assert!(lsv.repeat(0).is_empty());
+    }
+
+    #[test]
+    fn add() {
+        let a: LocalStorageVec<u8, 4> = LocalStorageVec::Stack {
+            buf: [1, 2, 0, 0],
+            len: 2,
+        };
+        let b: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(vec![3, 4]);
+
+        let mut combined = a + b + &[5u8][..];
+        assert_eq!(&combined[..], &[1, 2, 3, 4, 5]);
+
+        combined += &[6u8, 7][..];
+        combined += LocalStorageVec::<u8, 1>::Heap(vec![8]);
+        assert_eq!(&combined[..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn add_assign_reserves_once() {
+        let mut lsv: LocalStorageVec<u64, 1> = LocalStorageVec::Heap(Vec::new());
+        lsv += LocalStorageVec::<u64, 8>::from([1, 2, 3, 4, 5]);
+
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5]);
+        assert_eq!(lsv.capacity(), 5);
+    }
+
+    #[test]
+    fn extend_with_vectors() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.push(0);
+
+        let parts: [LocalStorageVec<u8, 2>; 2] = [
+            LocalStorageVec::Stack {
+                buf: [1, 2],
+                len: 2,
+            },
+            LocalStorageVec::Heap(vec![3, 4, 5]),
+        ];
+        lsv.extend(parts);
+
+        assert_eq!(&lsv[..], &[0, 1, 2, 3, 4, 5]);
+    }
+}
+
+// ------- SWAP -------
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Swap the contents of two vectors.
+    ///
+    /// Two heap vectors swap their buffer pointers, and two inline vectors only swap their live
+    /// elements. (Named `swap_with` because `swap(a, b)` is the slice method that swaps elements.)
+    pub fn swap_with(&mut self, other: &mut Self) {
+        match (self, other) {
+            (LocalStorageVec::Heap(a), LocalStorageVec::Heap(b)) => std::mem::swap(a, b),
+            (
+                LocalStorageVec::Stack { buf: a, len: len_a },
+                LocalStorageVec::Stack { buf: b, len: len_b },
+            ) => {
+                let live = (*len_a).max(*len_b);
+                a[..live].swap_with_slice(&mut b[..live]);
+                std::mem::swap(len_a, len_b);
+            }
+            (a, b) => std::mem::swap(a, b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_swap {
+    use super::*;
+
+    #[test]
+    fn swap_with() {
+        let mut a = LocalStorageVec::Stack {
+            buf: [1u8, 2, 3, 0],
+            len: 3,
+        };
+        let mut b = LocalStorageVec::Stack {
+            buf: [4u8, 0, 0, 0],
+            len: 1,
+        };
+
+        a.swap_with(&mut b);
+        assert_eq!((&a[..], &b[..]), (&[4][..], &[1, 2, 3][..]));
+
+        let mut c = LocalStorageVec::Heap(vec![5, 6, 7, 8, 9]);
+        a.swap_with(&mut c);
+        assert!(matches!(a, LocalStorageVec::Heap(_)));
+        assert_eq!((&a[..], &c[..]), (&[5, 6, 7, 8, 9][..], &[4][..]));
+
+        let mut d = LocalStorageVec::Heap(vec![10]);
+        a.swap_with(&mut d);
+        assert_eq!((&a[..], &d[..]), (&[10][..], &[5, 6, 7, 8, 9][..]));
+    }
+}
+
+// ------- SPARE CAPACITY -------
+
+#[cfg(not(feature = "safe-only"))]
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// The live elements, and the spare capacity after them.
+    ///
+    /// Write into the spare capacity, then call [`LocalStorageVec::set_len`] to make the written
+    /// elements part of the vector.
+    ///
+    /// Unlike `Vec::split_at_spare_mut`, this is `unsafe`: the spare slots of an inline vector are
+    /// not uninitialized memory but live `T`s that the vector still owns, and the `MaybeUninit`
+    /// view lets you overwrite them without dropping them, or de-initialize them.
+    ///
+    /// # Safety
+    ///
+    /// For an inline vector, every spare slot must still hold an initialized value when the borrow
+    /// ends: overwriting a slot with `MaybeUninit::write` is fine (the old value is leaked),
+    /// de-initializing it is not. The spare capacity of a heap vector has no such requirement.
+    pub unsafe fn split_at_spare_mut(&mut self) -> (&mut [T], &mut [std::mem::MaybeUninit<T>]) {
+        let (ptr, len, capacity) = match self {
+            LocalStorageVec::Stack { buf, len } => (buf.as_mut_ptr(), *len, N),
+            LocalStorageVec::Heap(v) => (v.as_mut_ptr(), v.len(), v.capacity()),
+        };
+
+        // SAFETY: both halves are in bounds of the buffer and don't overlap. `MaybeUninit<T>` has
+        // the layout of `T`, and the caller upholds the initialization requirement
+        unsafe {
+            let spare = ptr.add(len).cast::<std::mem::MaybeUninit<T>>();
+
+            (
+                std::slice::from_raw_parts_mut(ptr, len),
+                std::slice::from_raw_parts_mut(spare, capacity - len),
+            )
+        }
+    }
+
+    /// Set the length of the vector, without dropping or initializing anything.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most the capacity, and the elements in `old_len..new_len` must be
+    /// initialized. In an inline vector, the elements past `new_len` are not dropped until their
+    /// slot is overwritten or the vector is dropped.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        match self {
+            LocalStorageVec::Stack { len, .. } => *len = new_len,
+            // SAFETY: the caller guarantees the requirements of `Vec::set_len`
+            LocalStorageVec::Heap(v) => unsafe { v.set_len(new_len) },
+        }
+
+        self.debug_check_invariants();
+    }
+}
+
+#[cfg(all(test, not(feature = "safe-only")))]
+mod test_spare_capacity {
+    use super::*;
+
+    fn decode<const N: usize>(lsv: &mut LocalStorageVec<u32, N>, extra: usize) {
+        // SAFETY: every spare slot that is touched gets a value written into it
+        let (live, spare) = unsafe { lsv.split_at_spare_mut() };
+        let sum: u32 = live.iter().sum();
+
+        for (i, slot) in spare.iter_mut().take(extra).enumerate() {
+            slot.write(sum + i as u32);
+        }
+
+        let new_len = live.len() + extra.min(spare.len());
+
+        // SAFETY: the elements up to `new_len` were written above
+        unsafe { lsv.set_len(new_len) };
+    }
+
+    #[test]
+    fn split_at_spare_mut() {
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [1, 2, 0, 0],
+            len: 2,
+        };
+        decode(&mut lsv, 2);
+        assert_eq!(&lsv[..], &[1, 2, 3, 4]);
+
+        let mut lsv: LocalStorageVec<u32, 1> = LocalStorageVec::Heap(Vec::with_capacity(4));
+        lsv.extend([5]);
+        decode(&mut lsv, 3);
+        assert_eq!(&lsv[..], &[5, 5, 6, 7]);
+    }
+}
+
+// ------- DRAIN -------
+
+/// Resolve `range` against a slice of length `len`, panicking if it is out of bounds
+fn slice_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).expect("range start overflow"),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("range end overflow"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(start <= end, "range starts at {start} but ends at {end}");
+    assert!(
+        end <= len,
+        "range end (is {end}) should be <= len (is {len})"
+    );
+
+    start..end
+}
+
+/// Resolve `range` against a slice of length `len`, or an error if it is out of bounds
+fn try_slice_range<R: RangeBounds<usize>>(
+    range: R,
+    len: usize,
+) -> Result<Range<usize>, RangeError> {
+    // a saturated bound is out of bounds either way, because `len < usize::MAX`
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        return Err(RangeError { start, end, len });
+    }
+
+    Ok(start..end)
+}
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Removes the elements in `range`, yielding them. Elements that are not yielded are dropped
+    /// when the `Drain` is, unless [`Drain::keep_rest`] is used.
+    ///
+    /// Panics if the range is out of bounds.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the range is out of bounds, use `try_drain`")
+    )]
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, N> {
+        let Range { start, end } = slice_range(range, self.len());
+
+        Drain {
+            vec: self,
+            start,
+            next: start,
+            back: end,
+            end,
+        }
+    }
+
+    /// Removes and drops the elements in `range`, shifting the elements after it to the left.
+    ///
+    /// Panics if the range is out of bounds.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the range is out of bounds, use `try_remove_range`")
+    )]
+    pub fn remove_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        let range = slice_range(range, self.len());
+
+        match self {
+            LocalStorageVec::Stack { .. } => {
+                let removed = range.len();
+
+                self[range.start..].rotate_left(removed);
+                self.truncate(self.len() - removed);
+            }
+            LocalStorageVec::Heap(v) => {
+                v.drain(range);
+            }
+        }
+    }
+}
+
+/// A draining iterator, created by [`LocalStorageVec::drain`]
+pub struct Drain<'a, T: Default, const N: usize> {
+    vec: &'a mut LocalStorageVec<T, N>,
+    /// the start of the drained range
+    start: usize,
+    /// the next element to yield (elements before it have been taken)
+    next: usize,
+    /// one past the next element to yield from the back (elements from it have been taken)
+    back: usize,
+    /// the end of the drained range
+    end: usize,
+}
+
+impl<T: Default, const N: usize> Drain<'_, T, N> {
+    /// Keep the elements that were not yielded in the vector, instead of dropping them
+    pub fn keep_rest(mut self) {
+        self.vec.remove_range(self.back..self.end);
+
+        // only the elements yielded from the front are left to remove on drop
+        self.end = self.next;
+    }
+}
+
+impl<T: Default, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.back {
+            return None;
+        }
+
+        let value = std::mem::take(&mut self.vec[self.next]);
+        self.next += 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Default, const N: usize> DoubleEndedIterator for Drain<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.next == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(std::mem::take(&mut self.vec[self.back]))
+    }
+}
+
+impl<T: Default, const N: usize> ExactSizeIterator for Drain<'_, T, N> {}
+
+impl<T: Default, const N: usize> Drop for Drain<'_, T, N> {
+    fn drop(&mut self) {
+        self.vec.remove_range(self.start..self.end);
+    }
+}
+
+#[cfg(test)]
+mod test_drain {
+    use super::*;
+
+    #[test]
+    fn drain() {
+        let mut lsv: LocalStorageVec<u8, 8> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5]);
+
+        let drained: Vec<_> = lsv.drain(1..3).collect();
+        assert_eq!(drained, [2, 3]);
+        assert_eq!(&lsv[..], &[1, 4, 5]);
+
+        // elements that are not yielded are removed too
+        let mut drain = lsv.drain(..);
+        assert_eq!(drain.next(), Some(1));
+        drop(drain);
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    fn keep_rest() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5]);
+
+        let mut drain = lsv.drain(1..=3);
+        assert_eq!(drain.next(), Some(2));
+        drain.keep_rest();
+
+        assert_eq!(&lsv[..], &[1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5]);
+
+        let drained: Vec<_> = lsv.drain(1..4).rev().collect();
+        assert_eq!(drained, [4, 3, 2]);
+        assert_eq!(&lsv[..], &[1, 5]);
+
+        let mut lsv: LocalStorageVec<u8, 8> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5, 6]);
+
+        let mut drain = lsv.drain(..5);
+        assert_eq!(drain.len(), 5);
+        assert_eq!((drain.next(), drain.next_back()), (Some(1), Some(5)));
+        assert_eq!(drain.len(), 3);
+        drain.keep_rest();
+
+        assert_eq!(&lsv[..], &[2, 3, 4, 6]);
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut lsv: LocalStorageVec<u8, 8> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5]);
+        lsv.remove_range(1..3);
+        assert_eq!(&lsv[..], &[1, 4, 5]);
+
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(vec![1, 2, 3, 4, 5]);
+        lsv.remove_range(3..);
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+
+        lsv.remove_range(1..1);
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.extend([1, 2]);
+        lsv.drain(1..3);
+    }
+}
+
+// ------- PANIC SAFETY -------
+
+#[cfg(test)]
+mod test_panic_safety {
+    use super::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    /// Shares an `Rc` to count live values, and panics when cloned if its id is 0
+    #[derive(Debug, Default)]
+    struct Tracked(Rc<()>, u8);
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            assert_ne!(self.1, 0, "cloning a poisoned value");
+            Tracked(self.0.clone(), self.1)
+        }
+    }
+
+    fn ids<const N: usize>(lsv: &LocalStorageVec<Tracked, N>) -> Vec<u8> {
+        lsv.iter().map(|t| t.1).collect()
+    }
+
+    #[test]
+    fn extend_from_slice_keeps_cloned_prefix() {
+        let rc = Rc::new(());
+        let source = [1, 2, 0, 3].map(|id| Tracked(rc.clone(), id));
+
+        let mut inline: LocalStorageVec<Tracked, 8> = LocalStorageVec::new();
+        let result = catch_unwind(AssertUnwindSafe(|| inline.extend_from_slice(&source)));
+        assert!(result.is_err());
+        assert_eq!(ids(&inline), [1, 2]);
+
+        let mut heap: LocalStorageVec<Tracked, 2> = LocalStorageVec::new();
+        let result = catch_unwind(AssertUnwindSafe(|| heap.extend_from_slice(&source)));
+        assert!(result.is_err());
+        assert_eq!(ids(&heap), [1, 2]);
+
+        drop((inline, heap, source));
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn retain_panic_keeps_order() {
+        for spilled in [false, true] {
+            let rc = Rc::new(());
+
+            let mut lsv: LocalStorageVec<Tracked, 5> = LocalStorageVec::new();
+            lsv.extend((1..=5).map(|id| Tracked(rc.clone(), id)));
+            if spilled {
+                lsv.reserve(1);
+            }
+
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                lsv.retain(|t| {
+                    assert_ne!(t.1, 4);
+                    t.1 % 2 == 1
+                })
+            }));
+
+            // the rejected element is dropped, unprocessed elements are kept
+            assert!(result.is_err());
+            assert_eq!(ids(&lsv), [1, 3, 4, 5]);
+            assert_eq!(Rc::strong_count(&rc), 5);
+        }
+    }
+
+    #[test]
+    fn truncate_panicking_drop() {
+        #[derive(Default)]
+        struct PanicOnDrop(bool);
+
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                if self.0 {
+                    panic!("drop");
+                }
+            }
+        }
+
+        let mut lsv: LocalStorageVec<PanicOnDrop, 4> = LocalStorageVec::new();
+        lsv.extend([PanicOnDrop(false), PanicOnDrop(true)]);
+
+        let result = catch_unwind(AssertUnwindSafe(|| lsv.truncate(0)));
+        assert!(result.is_err());
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    fn spill_does_not_call_default() {
+        thread_local! {
+            static DEFAULTS_ALLOWED: std::cell::Cell<bool> = const { std::cell::Cell::new(true) };
+        }
+
+        struct Fussy(u8);
+
+        impl Default for Fussy {
+            fn default() -> Self {
+                assert!(DEFAULTS_ALLOWED.get(), "default called");
+                Fussy(0)
+            }
+        }
+
+        let mut lsv: LocalStorageVec<Fussy, 2> = LocalStorageVec::new();
+        DEFAULTS_ALLOWED.set(false);
+
+        lsv.push(Fussy(1));
+        lsv.push(Fussy(2));
+        lsv.push(Fussy(3));
+
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(lsv.iter().map(|f| f.0).collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn panicking_iterator_across_spill() {
+        let rc = Rc::new(());
+
+        let mut lsv: LocalStorageVec<Tracked, 2> = LocalStorageVec::new();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            lsv.extend((1..).map(|id| {
+                assert!(id < 4);
+                Tracked(rc.clone(), id)
+            }))
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(ids(&lsv), [1, 2, 3]);
+
+        drop(lsv);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
+
+// ------- CHECKED -------
+
+// Variants of the operations that can panic, which return a `Result` instead. They never grow
+// the vector: exceeding the current capacity is an error rather than a reason to spill or grow.
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Appends `value` if it fits in the current capacity
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.push(value);
+        Ok(())
+    }
+
+    /// Inserts `element` at `index` if the index is valid and it fits in the current capacity
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<(), TryInsertError<T>> {
+        let len = self.len();
+
+        if index > len {
+            let error = IndexError { index, len };
+            return Err(TryInsertError::Index(error, element));
+        }
+
+        if self.is_full() {
+            return Err(TryInsertError::Capacity(CapacityError::new(element)));
+        }
+
+        self.push(element);
+        self[index..].rotate_right(1);
+
+        Ok(())
+    }
+
+    /// Removes and returns the element at `index`, if the index is valid
+    pub fn try_remove(&mut self, index: usize) -> Result<T, IndexError> {
+        let len = self.len();
+
+        if index >= len {
+            return Err(IndexError { index, len });
+        }
+
+        self[index..].rotate_left(1);
+        Ok(self.pop().unwrap())
+    }
+
+    /// Removes the elements in `range` (see [`LocalStorageVec::drain`]), if the range is valid
+    pub fn try_drain<R: RangeBounds<usize>>(
+        &mut self,
+        range: R,
+    ) -> Result<Drain<'_, T, N>, RangeError> {
+        let range = try_slice_range(range, self.len())?;
+        Ok(self.drain(range))
+    }
+
+    /// Removes and drops the elements in `range`, if the range is valid
+    pub fn try_remove_range<R: RangeBounds<usize>>(&mut self, range: R) -> Result<(), RangeError> {
+        let range = try_slice_range(range, self.len())?;
+        self.remove_range(range);
+        Ok(())
+    }
+
+    /// Removes the first `at` elements and returns them (see [`LocalStorageVec::split_to`]), if
+    /// `at` is at most the length
+    pub fn try_split_to(&mut self, at: usize) -> Result<Self, IndexError> {
+        let len = self.len();
+
+        if at > len {
+            return Err(IndexError { index: at, len });
+        }
+
+        Ok(self.split_to(at))
+    }
+
+    /// Resizes to `new_len` elements (see [`LocalStorageVec::resize`]) if that fits in the current
+    /// capacity
+    pub fn try_resize(&mut self, new_len: usize, value: T) -> Result<(), CapacityError<T>>
+    where
+        T: Clone,
+    {
+        if new_len > self.capacity() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.resize(new_len, value);
+        Ok(())
+    }
+
+    /// Clones and appends all elements of `other` if they fit in the current capacity. Nothing is
+    /// appended otherwise; [`LocalStorageVec::remaining_capacity`] is how many would fit.
+    pub fn try_extend_from_slice<'a>(
+        &mut self,
+        other: &'a [T],
+    ) -> Result<(), CapacityError<&'a [T]>>
+    where
+        T: Clone,
+    {
+        if other.len() > self.remaining_capacity() {
+            return Err(CapacityError::new(other));
+        }
+
+        self.extend_from_slice(other);
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// The element at `index`, or an error with the index and length
+    pub fn checked_index(&self, index: usize) -> Result<&T, IndexError> {
+        let len = self.len();
+        self.get(index).ok_or(IndexError { index, len })
+    }
+
+    /// The element at `index`, or an error with the index and length
+    pub fn checked_index_mut(&mut self, index: usize) -> Result<&mut T, IndexError> {
+        let len = self.len();
+        self.get_mut(index).ok_or(IndexError { index, len })
+    }
+}
+
+#[cfg(test)]
+mod test_checked {
+    use super::*;
+
+    #[test]
+    fn try_drain_and_remove_range() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2, 3, 4]);
+
+        let error = RangeError {
+            start: 2,
+            end: 5,
+            len: 4,
+        };
+        assert!(matches!(lsv.try_drain(2..5), Err(e) if e == error));
+        assert!(lsv.try_drain(3..=usize::MAX).is_err());
+        assert_eq!(lsv.try_drain(1..=2).unwrap().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(&lsv[..], &[1, 4]);
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = lsv.try_remove_range(2..1);
+        assert!(reversed.is_err());
+        assert_eq!(lsv.try_remove_range(..1), Ok(()));
+        assert_eq!(&lsv[..], &[4]);
+    }
+
+    #[test]
+    fn try_split_to() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2, 3]);
+
+        let error = IndexError { index: 4, len: 3 };
+        assert_eq!(lsv.try_split_to(4), Err(error));
+        assert_eq!(&lsv.try_split_to(2).unwrap()[..], &[1, 2]);
+        assert_eq!(&lsv[..], &[3]);
+    }
+
+    #[test]
+    fn try_extend_from_slice_is_atomic() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+
+        assert_eq!(lsv.try_extend_from_slice(&[1, 2]), Ok(()));
+
+        let error = lsv.try_extend_from_slice(&[3, 4, 5]).unwrap_err();
+        assert_eq!(error.into_inner(), &[3, 4, 5]);
+        assert_eq!(&lsv[..], &[1, 2]);
+        assert_eq!(lsv.remaining_capacity(), 2);
+
+        assert_eq!(lsv.try_extend_from_slice(&[3, 4]), Ok(()));
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 4, .. }));
+    }
+
+    #[test]
+    fn try_push_never_spills() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+
+        assert_eq!(lsv.try_push(1), Ok(()));
+        assert_eq!(lsv.try_push(2), Ok(()));
+        assert_eq!(lsv.try_push(3).unwrap_err().into_inner(), 3);
+        assert!(matches!(lsv, LocalStorageVec::Stack { .. }));
+    }
+
+    #[test]
+    fn try_insert_and_remove() {
+        let mut lsv: LocalStorageVec<u8, 3> = LocalStorageVec::new();
+        lsv.extend([1, 3]);
+
+        assert_eq!(lsv.try_insert(1, 2), Ok(()));
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+
+        let error = IndexError { index: 5, len: 3 };
+        assert_eq!(lsv.try_insert(5, 4), Err(TryInsertError::Index(error, 4)));
+        assert!(matches!(
+            lsv.try_insert(0, 4),
+            Err(TryInsertError::Capacity(_))
+        ));
+
+        assert_eq!(lsv.try_remove(0), Ok(1));
+        assert_eq!(lsv.try_remove(2), Err(IndexError { index: 2, len: 2 }));
+        assert_eq!(&lsv[..], &[2, 3]);
+    }
+
+    #[test]
+    fn try_resize() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+
+        assert_eq!(lsv.try_resize(3, 7), Ok(()));
+        assert_eq!(&lsv[..], &[7, 7, 7]);
+
+        assert!(lsv.try_resize(5, 0).is_err());
+        assert_eq!(lsv.try_resize(1, 0), Ok(()));
+        assert_eq!(&lsv[..], &[7]);
+
+        lsv.resize(6, 1);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(&lsv[..], &[7, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn checked_index() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.push(1);
+
+        *lsv.checked_index_mut(0).unwrap() += 1;
+        assert_eq!(lsv.checked_index(0), Ok(&2));
+        assert_eq!(lsv.checked_index(1), Err(IndexError { index: 1, len: 1 }));
+    }
+}
+
+// ------- SORT -------
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// A stable sort that doesn't allocate for inline vectors: it merges through an inline
+    /// scratch buffer of `N` elements instead of the heap buffer that `slice::sort` uses.
+    ///
+    /// Heap vectors use `slice::sort`.
+    pub fn sort_stable_inline(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_stable_inline_by(T::cmp);
+    }
+
+    /// Like [`LocalStorageVec::sort_stable_inline`], with a comparison function
+    pub fn sort_stable_inline_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let mut scratch: [T; N] = std::array::from_fn(|_| T::default());
+                merge_sort(&mut buf[..*len], &mut scratch, &mut |a, b| {
+                    compare(a, b) == Ordering::Less
+                });
+            }
+            LocalStorageVec::Heap(v) => v.sort_by(compare),
+        }
+    }
+}
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Inserts `value` into a sorted vector, after any equal elements, and returns its index
+    pub fn insert_sorted(&mut self, value: T) -> usize
+    where
+        T: Ord,
+    {
+        let index = self.partition_point(|e| e <= &value);
+        self.insert(index, value);
+        index
+    }
+
+    /// Inserts `value` into a vector sorted by `key`, after any elements with an equal key, and
+    /// returns its index
+    pub fn insert_sorted_by_key<K: Ord, F: FnMut(&T) -> K>(
+        &mut self,
+        value: T,
+        mut key: F,
+    ) -> usize {
+        let value_key = key(&value);
+        let index = self.partition_point(|e| key(e) <= value_key);
+        self.insert(index, value);
+        index
+    }
+}
+
+/// Stable merge sort of `v`, using `scratch` (at least half as long as `v`) to hold a run
+fn merge_sort<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], scratch: &mut [T], is_less: &mut F) {
+    const INSERTION_SORT_LEN: usize = 16;
+
+    let len = v.len();
+    if len <= INSERTION_SORT_LEN {
+        for i in 1..len {
+            let mut j = i;
+            while j > 0 && is_less(&v[j], &v[j - 1]) {
+                v.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+
+        return;
+    }
+
+    let mid = len / 2;
+    merge_sort(&mut v[..mid], scratch, is_less);
+    merge_sort(&mut v[mid..], scratch, is_less);
+
+    if !is_less(&v[mid], &v[mid - 1]) {
+        return;
+    }
+
+    // move the left run into the scratch buffer, then merge back into `v`. The slots in `k..j`
+    // hold whatever was in the scratch buffer, and are exactly as many as the elements of the
+    // left run that haven't been merged yet, so the guard can swap those back if `is_less` panics
+    struct Merge<'a, T> {
+        v: &'a mut [T],
+        scratch: &'a mut [T],
+        mid: usize,
+        i: usize,
+        j: usize,
+        k: usize,
+    }
+
+    impl<T> Drop for Merge<'_, T> {
+        fn drop(&mut self) {
+            let remaining = self.mid - self.i;
+            self.v[self.k..self.k + remaining].swap_with_slice(&mut self.scratch[self.i..self.mid]);
+        }
+    }
+
+    v[..mid].swap_with_slice(&mut scratch[..mid]);
+
+    let mut merge = Merge {
+        v,
+        scratch,
+        mid,
+        i: 0,
+        j: mid,
+        k: 0,
+    };
+
+    while merge.i < mid && merge.j < len {
+        let Merge {
+            v,
+            scratch,
+            i,
+            j,
+            k,
+            ..
+        } = &mut merge;
+
+        // take from the left run on ties, which keeps the sort stable
+        if is_less(&v[*j], &scratch[*i]) {
+            v.swap(*k, *j);
+            *j += 1;
+        } else {
+            std::mem::swap(&mut v[*k], &mut scratch[*i]);
+            *i += 1;
+        }
+
+        *k += 1;
+    }
+
+    // the guard moves the rest of the left run into place
+}
+
+#[cfg(test)]
+mod test_sort {
+    use super::*;
+
+    /// A deterministic shuffle of `0..len`, tagged with the original index
+    fn shuffled(len: usize) -> Vec<(u8, usize)> {
+        let mut state = 0x2545F491u32;
+        (0..len)
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                ((state % 8) as u8, i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_stable_inline() {
+        for len in [0, 1, 2, 15, 16, 17, 33, 64] {
+            let input = shuffled(len);
+
+            let mut lsv: LocalStorageVec<(u8, usize), 64> = LocalStorageVec::new();
+            lsv.extend_from_slice(&input);
+
+            // only compare the keys, the tags check stability
+            lsv.sort_stable_inline_by(|a, b| a.0.cmp(&b.0));
+
+            let mut expected = input.clone();
+            expected.sort_by_key(|x| x.0);
+            assert_eq!(&lsv[..], &expected[..], "len {len}");
+        }
+
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(vec![3, 1, 2]);
+        lsv.sort_stable_inline();
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn insert_sorted() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+
+        for (value, index) in [(5, 0), (1, 0), (3, 1), (5, 3), (0, 0)] {
+            assert_eq!(lsv.insert_sorted(value), index);
+        }
+        assert_eq!(&lsv[..], &[0, 1, 3, 5, 5]);
+
+        let mut lsv: LocalStorageVec<(u8, char), 4> = LocalStorageVec::new();
+        for value in [(2, 'a'), (1, 'b'), (2, 'c')] {
+            lsv.insert_sorted_by_key(value, |x| x.0);
+        }
+        assert_eq!(&lsv[..], &[(1, 'b'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn panicking_comparison_keeps_elements() {
+        let input = shuffled(40);
+
+        let mut lsv: LocalStorageVec<(u8, usize), 64> = LocalStorageVec::new();
+        lsv.extend_from_slice(&input);
+
+        let mut comparisons = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            lsv.sort_stable_inline_by(|a, b| {
+                comparisons += 1;
+                assert!(comparisons < 100);
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+
+        let mut elements = lsv.to_vec();
+        elements.sort();
+        let mut expected = input;
+        expected.sort();
+        assert_eq!(elements, expected);
+    }
+}
+
+// ------- PARTITION -------
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Split into the elements for which `f` returns `true` and those for which it returns
+    /// `false`, preserving their order. Each half is inline if it fits.
+    pub fn partition<F: FnMut(&T) -> bool>(self, mut f: F) -> (Self, Self) {
+        let mut matching = Self::new();
+        let mut rest = Self::new();
+
+        for value in self {
+            if f(&value) {
+                matching.push(value);
+            } else {
+                rest.push(value);
+            }
+        }
+
+        (matching, rest)
+    }
+}
+
+#[cfg(test)]
+mod test_partition {
+    use super::*;
+
+    #[test]
+    fn partition() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3, 4, 5, 7]);
+
+        let (odd, even) = lsv.partition(|x| x % 2 == 1);
+
+        assert_eq!(&odd[..], &[1, 3, 5, 7]);
+        assert_eq!(&even[..], &[2, 4]);
+        assert!(matches!(odd, LocalStorageVec::Stack { .. }));
+        assert!(matches!(even, LocalStorageVec::Stack { .. }));
+    }
+}
+
+// ------- CONVERSIONS -------
+
+/// Moves the elements inline if `K <= N`, and into a heap buffer of exactly `K` elements otherwise
+impl<T: Default, const N: usize, const K: usize> From<[T; K]> for LocalStorageVec<T, N> {
+    fn from(array: [T; K]) -> Self {
+        if K <= N {
+            #[cfg(feature = "safe-only")]
+            let buf = {
+                let mut values = array.into_iter();
+                std::array::from_fn(|_| values.next().unwrap_or_default())
+            };
+
+            #[cfg(not(feature = "safe-only"))]
+            let buf = {
+                let mut buf = std::mem::MaybeUninit::<[T; N]>::uninit();
+                let dst = buf.as_mut_ptr().cast::<T>();
+
+                // fill the unused slots first: if `T::default` panics, `array` is still owned
+                // here and dropped normally (the defaults written so far are leaked)
+                for i in K..N {
+                    // SAFETY: `i < N`, so the slot is in bounds
+                    unsafe { dst.add(i).write(T::default()) };
+                }
+
+                // then move all `K` elements with one copy
+                let array = ManuallyDrop::new(array);
+
+                // SAFETY: `K <= N`, so the elements fit in front of the slots written above, and
+                // `array` is not dropped, so each element has a single owner. Now all `N` slots
+                // are initialized
+                unsafe {
+                    ptr::copy_nonoverlapping(array.as_ptr(), dst, K);
+                    buf.assume_init()
+                }
+            };
+
+            LocalStorageVec::Stack { buf, len: K }
+        } else {
+            let mut v = Vec::with_capacity(K);
+            v.extend(array);
+
+            LocalStorageVec::Heap(v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_conversions {
+    use super::*;
+
+    #[test]
+    fn from_array() {
+        let lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2, 3]);
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+
+        let lsv: LocalStorageVec<u8, 4> = [1, 2, 3, 4, 5].into();
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(lsv.capacity(), 5);
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5]);
+
+        let lsv: LocalStorageVec<String, 2> = [].into();
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    fn from_array_defaults_only_the_tail() {
+        thread_local! {
+            static DEFAULTS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Counted(String);
+
+        impl Default for Counted {
+            fn default() -> Self {
+                DEFAULTS.set(DEFAULTS.get() + 1);
+                Counted(String::new())
+            }
+        }
+
+        let values = ["a", "b", "c"].map(|s| Counted(s.to_string()));
+        let lsv: LocalStorageVec<Counted, 5> = LocalStorageVec::from(values);
+
+        assert_eq!(DEFAULTS.get(), 2);
+        assert!(lsv.iter().map(|c| c.0.as_str()).eq(["a", "b", "c"]));
+    }
+}
+
+// ------- SPLIT -------
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Removes the first `at` elements and returns them, shifting the rest to the front once.
+    ///
+    /// The returned vector is inline if `at <= N`. Panics if `at > len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the index is out of bounds, use `try_split_to`")
+    )]
+    pub fn split_to(&mut self, at: usize) -> Self {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "split index (is {at}) should be <= len (is {len})"
+        );
+
+        let mut front = Self::with_capacity(at);
+        front.extend(self.drain(..at));
+        front
+    }
+}
+
+#[cfg(test)]
+mod test_split {
+    use super::*;
+
+    #[test]
+    fn split_to() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2, 3, 4]);
+
+        let front = lsv.split_to(1);
+        assert!(matches!(front, LocalStorageVec::Stack { len: 1, .. }));
+        assert_eq!((&front[..], &lsv[..]), (&[1][..], &[2, 3, 4][..]));
+
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        let front = lsv.split_to(3);
+        assert!(matches!(front, LocalStorageVec::Heap(_)));
+        assert_eq!((&front[..], &lsv[..]), (&[1, 2, 3][..], &[4, 5][..]));
+
+        assert!(lsv.split_to(0).is_empty());
+        assert_eq!(&lsv.split_to(2)[..], &[4, 5]);
+        assert!(lsv.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "split index (is 3) should be <= len (is 2)")]
+    fn split_to_out_of_bounds() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2]);
+        lsv.split_to(3);
+    }
+}
+
+// ------- CHUNKS -------
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Consume the vector, yielding owned chunks of `chunk_size` elements (the last one may be
+    /// shorter). The chunks are inline when `chunk_size <= M`.
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn into_chunks<const M: usize>(self, chunk_size: usize) -> IntoChunks<T, N, M> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+
+        IntoChunks {
+            iter: self.into_iter(),
+            chunk_size,
+        }
+    }
+}
+
+/// The iterator of [`LocalStorageVec::into_chunks`]
+pub struct IntoChunks<T, const N: usize, const M: usize> {
+    iter: IntoIter<T, N>,
+    chunk_size: usize,
+}
+
+impl<T: Default, const N: usize, const M: usize> Iterator for IntoChunks<T, N, M> {
+    type Item = LocalStorageVec<T, M>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let size = self.chunk_size.min(self.iter.len());
+        if size == 0 {
+            return None;
+        }
+
+        let mut chunk = LocalStorageVec::with_capacity(size);
+        chunk.extend(self.iter.by_ref().take(size));
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let chunks = self.iter.len().div_ceil(self.chunk_size);
+        (chunks, Some(chunks))
+    }
+}
+
+impl<T: Default, const N: usize, const M: usize> ExactSizeIterator for IntoChunks<T, N, M> {}
+
+#[cfg(test)]
+mod test_chunks {
+    use super::*;
+
+    #[test]
+    fn into_chunks() {
+        let lsv: LocalStorageVec<u8, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+
+        let mut chunks = lsv.clone().into_chunks::<2>(2);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(&chunks.next().unwrap()[..], &[1, 2]);
+        assert_eq!(&chunks.next().unwrap()[..], &[3, 4]);
+
+        let last = chunks.next().unwrap();
+        assert!(matches!(last, LocalStorageVec::Stack { len: 1, .. }));
+        assert_eq!(&last[..], &[5]);
+        assert!(chunks.next().is_none());
+
+        let mut chunks = lsv.into_chunks::<2>(4);
+        assert!(matches!(chunks.next().unwrap(), LocalStorageVec::Heap(_)));
+        assert_eq!(chunks.len(), 1);
+
+        let empty: LocalStorageVec<u8, 8> = LocalStorageVec::new();
+        assert_eq!(empty.into_chunks::<2>(3).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be non-zero")]
+    fn zero_chunk_size() {
+        let lsv: LocalStorageVec<u8, 8> = LocalStorageVec::from([1]);
+        lsv.into_chunks::<1>(0);
+    }
+}
+
+// ------- ZEROED -------
+
+/// Types for which all-zero bytes are a valid value, like `bytemuck::Zeroable`
+pub trait Zeroable: Copy {
+    const ZERO: Self;
+}
+
+macro_rules! impl_zeroable {
+    ($($ty:ty),*) => {
+        $(
+            impl Zeroable for $ty {
+                const ZERO: Self = 0 as $ty;
+            }
+        )*
+    };
+}
+
+impl_zeroable!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl<T: Zeroable, const N: usize> LocalStorageVec<T, N> {
+    /// A vector of `len` zeroes. A spilled vector uses a zeroed allocation, so the zeroes
+    /// aren't written one by one.
+    pub fn zeroed(len: usize) -> Self {
+        if len <= N {
+            LocalStorageVec::Stack {
+                buf: [T::ZERO; N],
+                len,
+            }
+        } else {
+            // `vec!` of a zero primitive uses `alloc_zeroed`
+            LocalStorageVec::Heap(vec![T::ZERO; len])
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_zeroed {
+    use super::*;
+
+    #[test]
+    fn zeroed() {
+        let lsv: LocalStorageVec<u8, 4> = LocalStorageVec::zeroed(3);
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(&lsv[..], &[0, 0, 0]);
+
+        let lsv: LocalStorageVec<f32, 4> = LocalStorageVec::zeroed(1000);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(lsv.len(), 1000);
+        assert!(lsv.iter().all(|&x| x == 0.0));
+    }
+}
+
+// ------- GROW -------
+
+impl<T: Default, const N: usize> LocalStorageVec<T, N> {
+    /// Set the capacity to (at least) `new_capacity`, in either direction.
+    ///
+    /// A capacity of at most `N` moves the elements inline. A larger capacity moves them into a
+    /// heap buffer of that size, reallocating a heap buffer of a different size.
+    ///
+    /// Panics if `new_capacity < len`.
+    #[cfg_attr(
+        feature = "deprecate-panicking",
+        deprecated(note = "panics if the capacity is below the length, use `try_grow`")
+    )]
+    pub fn grow(&mut self, new_capacity: usize) {
+        let len = self.len();
+        assert!(
+            new_capacity >= len,
+            "new capacity (is {new_capacity}) should be >= len (is {len})"
+        );
+
+        match self {
+            _ if new_capacity <= N => self.demote(),
+            LocalStorageVec::Stack { .. } => self.spill(Vec::with_capacity(new_capacity)),
+            LocalStorageVec::Heap(v) => resize_heap(v, new_capacity),
+        }
+
+        self.debug_check_invariants();
+    }
+
+    /// Like [`LocalStorageVec::grow`], but returns an error instead of panicking (or aborting)
+    /// when `new_capacity < len` or the allocation fails
+    pub fn try_grow(&mut self, new_capacity: usize) -> Result<(), TryReserveError> {
+        let len = self.len();
+        if new_capacity < len {
+            return Err(TryReserveError::BelowLength {
+                capacity: new_capacity,
+                len,
+            });
+        }
+
+        match self {
+            _ if new_capacity <= N => self.demote(),
+            LocalStorageVec::Stack { .. } => {
+                let mut v = Vec::new();
+                v.try_reserve_exact(new_capacity)?;
+                self.spill(v);
+            }
+            LocalStorageVec::Heap(v) => {
+                let capacity = v.capacity();
+
+                if new_capacity > capacity {
+                    v.try_reserve_exact(new_capacity - len)?;
+                } else {
+                    v.shrink_to(new_capacity);
+                }
+
+                metrics::record_growth(capacity, v.capacity());
+            }
+        }
+
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Move the elements of a heap vector into the inline buffer, which must fit them
+    fn demote(&mut self) {
+        if let LocalStorageVec::Heap(v) = self {
+            let len = v.len();
+            let mut buf: [T; N] = std::array::from_fn(|_| T::default());
+
+            for (slot, value) in buf.iter_mut().zip(v.drain(..)) {
+                *slot = value;
+            }
+
+            *self = LocalStorageVec::Stack { buf, len };
+            metrics::record_demotion();
+        }
+    }
+}
+
+/// Reallocate `v` to (at least) `new_capacity`, which is at least its length
+fn resize_heap<T>(v: &mut Vec<T>, new_capacity: usize) {
+    let capacity = v.capacity();
+
+    if new_capacity > capacity {
+        v.reserve_exact(new_capacity - v.len());
+    } else {
+        v.shrink_to(new_capacity);
+    }
+
+    metrics::record_growth(capacity, v.capacity());
+}
+
+#[cfg(test)]
+mod test_grow {
+    use super::*;
+
+    #[test]
+    fn grow() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2, 3]);
+
+        lsv.grow(10);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(lsv.capacity(), 10);
+
+        lsv.grow(20);
+        assert!(lsv.capacity() >= 20);
+
+        lsv.grow(5);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert!((5..20).contains(&lsv.capacity()));
+
+        lsv.grow(3);
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(&lsv[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "new capacity (is 1) should be >= len (is 3)")]
+    fn grow_below_len() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::from([1, 2, 3]);
+        lsv.grow(1);
+    }
+
+    #[test]
+    fn try_grow() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::from([1, 2, 3]);
+
+        assert_eq!(
+            lsv.try_grow(2),
+            Err(TryReserveError::BelowLength {
+                capacity: 2,
+                len: 3
+            })
+        );
+        assert!(matches!(
+            lsv.try_grow(usize::MAX),
+            Err(TryReserveError::Heap(_))
+        ));
+
+        lsv.pop();
+        assert_eq!(lsv.try_grow(2), Ok(()));
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 2, .. }));
+
+        assert_eq!(lsv.try_grow(8), Ok(()));
+        assert_eq!(lsv.capacity(), 8);
+        assert_eq!(&lsv[..], &[1, 2]);
+    }
+}
+
+// ------- MAYBE UNINIT -------
+
+#[cfg(not(feature = "safe-only"))]
+impl<T, const N: usize> LocalStorageVec<std::mem::MaybeUninit<T>, N> {
+    /// A vector of `len` uninitialized elements: inline if `len <= N`, on the heap otherwise.
+    ///
+    /// Write the elements, then call [`assume_init`](LocalStorageVec::assume_init).
+    pub fn uninit(len: usize) -> Self {
+        if len <= N {
+            LocalStorageVec::Stack {
+                buf: [const { std::mem::MaybeUninit::uninit() }; N],
+                len,
+            }
+        } else {
+            let mut v = Vec::with_capacity(len);
+            v.resize_with(len, std::mem::MaybeUninit::uninit);
+            LocalStorageVec::Heap(v)
+        }
+    }
+
+    /// Converts to a vector of initialized elements, without creating any placeholder values.
+    ///
+    /// A heap vector keeps its buffer, and a full inline vector stays inline. The unused slots of
+    /// an inline vector that is not full can't be filled without a placeholder, so its elements
+    /// are moved to a heap buffer of capacity `N`.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` elements must be initialized.
+    pub unsafe fn assume_init(self) -> LocalStorageVec<T, N> {
+        match self {
+            LocalStorageVec::Stack { buf, len } if len == N => LocalStorageVec::Stack {
+                // SAFETY: `MaybeUninit<T>` has the layout of `T`, and the caller guarantees that
+                // all `N` elements are initialized
+                buf: unsafe { buf.as_ptr().cast::<[T; N]>().read() },
+                len,
+            },
+            LocalStorageVec::Stack { buf, len } => {
+                let mut v = Vec::with_capacity(N);
+
+                // SAFETY: the caller guarantees the first `len <= N` elements are initialized,
+                // and they are moved out exactly once: dropping `buf` drops nothing
+                unsafe {
+                    ptr::copy_nonoverlapping(buf.as_ptr().cast::<T>(), v.as_mut_ptr(), len);
+                    v.set_len(len);
+                }
+
+                LocalStorageVec::Heap(v)
+            }
+            LocalStorageVec::Heap(v) => {
+                let mut v = ManuallyDrop::new(v);
+                let (ptr, len, capacity) = (v.as_mut_ptr(), v.len(), v.capacity());
+
+                // SAFETY: `MaybeUninit<T>` has the layout of `T`, so the allocation is valid for a
+                // `Vec<T>` of the same capacity, and the caller guarantees the elements are
+                // initialized. `v` is not dropped, so the allocation has a single owner
+                LocalStorageVec::Heap(unsafe {
+                    Vec::from_raw_parts(ptr.cast::<T>(), len, capacity)
+                })
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "safe-only")))]
+mod test_maybe_uninit {
+    use super::*;
+
+    /// not `Default`, so no placeholder can be made for it
+    struct Label(String);
+
+    fn fill<const N: usize>(len: usize) -> LocalStorageVec<Label, N> {
+        let mut lsv = LocalStorageVec::<std::mem::MaybeUninit<Label>, N>::uninit(len);
+        for (i, slot) in lsv.iter_mut().enumerate() {
+            slot.write(Label(i.to_string()));
+        }
+
+        // SAFETY: every element was written above
+        unsafe { lsv.assume_init() }
+    }
+
+    fn labels<const N: usize>(lsv: &LocalStorageVec<Label, N>) -> Vec<&str> {
+        lsv.iter().map(|label| label.0.as_str()).collect()
+    }
+
+    #[test]
+    fn assume_init_full_inline() {
+        let lsv = fill::<3>(3);
+
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(labels(&lsv), ["0", "1", "2"]);
+    }
+
+    #[test]
+    fn assume_init_partial_inline() {
+        let lsv = fill::<4>(3);
+
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(lsv.capacity(), 4);
+        assert_eq!(labels(&lsv), ["0", "1", "2"]);
+    }
+
+    #[test]
+    fn assume_init_heap() {
+        let lsv = fill::<2>(5);
+
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(labels(&lsv), ["0", "1", "2", "3", "4"]);
+    }
+}
+
+// ------- OUTLINED -------
+
+// The helpers below don't depend on `N` (and the panics not on `T` either), so they are compiled
+// once instead of once per `LocalStorageVec<T, N>` instantiation.
+
+#[cfg(not(feature = "safe-only"))]
+/// Move `len` elements starting at `src` to the end of `v`
+///
+/// # Safety
+///
+/// `src` must be valid for reading `len` elements, which the caller must not use afterwards.
+unsafe fn move_to_heap<T>(src: *const T, len: usize, v: &mut Vec<T>) {
+    v.reserve(len);
+
+    // SAFETY: `v` has room for `len` more elements, and they are initialized by the copy
+    unsafe {
+        ptr::copy_nonoverlapping(src, v.as_mut_ptr().add(v.len()), len);
+        v.set_len(v.len() + len);
+    }
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn capacity_overflow() -> ! {
+    panic!("capacity overflow")
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn insert_index_out_of_bounds(index: usize, len: usize) -> ! {
+    panic!("insertion index (is {index}) should be <= len (is {len})")
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn remove_index_out_of_bounds(index: usize, len: usize) -> ! {
+    panic!("removal index (is {index}) should be < len (is {len})")
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn inline_length_out_of_bounds(len: usize, n: usize) -> ! {
+    panic!("inline length (is {len}) should be <= N (is {n})")
+}
+
+#[cfg(test)]
+mod test_outlined {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 3) should be <= len (is 1)")]
+    fn insert_message() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.push(1);
+        lsv.insert(3, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 1) should be < len (is 1)")]
+    fn remove_message() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.push(1);
+        lsv.remove(1);
+    }
+
+    #[test]
+    fn spill_moves_live_elements_and_drops_the_rest() {
+        use std::rc::Rc;
+
+        let live = Rc::new(());
+        let dead = Rc::new(());
+        let mut lsv = LocalStorageVec::Stack {
+            buf: [live.clone(), live.clone(), dead.clone()],
+            len: 2,
+        };
+
+        lsv.reserve(5);
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(Rc::strong_count(&live), 3);
+        assert_eq!(Rc::strong_count(&dead), 1);
+    }
 }
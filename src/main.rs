@@ -0,0 +1,112 @@
+//! Measures `LocalStorageVec` workloads, to help pick an inline capacity.
+//!
+//! ```text
+//! cargo run --release -- [inline capacity] [elements] [iterations]
+//! ```
+
+use std::hint::black_box;
+use std::mem::size_of;
+use std::time::{Duration, Instant};
+
+use stackvec::LocalStorageVec;
+
+type Element = u64;
+
+const USAGE: &str = "usage: stackvec [inline capacity: 4|8|16|32|64|128] [elements] [iterations]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let arg = |i: usize, default: usize| match args.get(i) {
+        None => default,
+        Some(arg) => arg.parse().unwrap_or_else(|_| {
+            eprintln!("invalid number {arg:?}\n{USAGE}");
+            std::process::exit(1);
+        }),
+    };
+
+    let (n, elements, iterations) = (arg(0, 16), arg(1, 12), arg(2, 100_000));
+
+    match n {
+        4 => report::<4>(elements, iterations),
+        8 => report::<8>(elements, iterations),
+        16 => report::<16>(elements, iterations),
+        32 => report::<32>(elements, iterations),
+        64 => report::<64>(elements, iterations),
+        128 => report::<128>(elements, iterations),
+        _ => {
+            eprintln!("unsupported inline capacity {n}\n{USAGE}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn report<const N: usize>(elements: usize, iterations: usize) {
+    let source: Vec<Element> = (0..elements as Element).collect();
+
+    let mut lsv: LocalStorageVec<Element, N> = LocalStorageVec::new();
+    lsv.extend_from_slice(&source);
+
+    let spilled = matches!(lsv, LocalStorageVec::Heap(_));
+    let heap_bytes = if spilled {
+        lsv.capacity() * size_of::<Element>()
+    } else {
+        0
+    };
+
+    println!("LocalStorageVec<u64, {N}> holding {elements} elements");
+    println!(
+        "  inline size: {} bytes",
+        size_of::<LocalStorageVec<Element, N>>()
+    );
+    println!("  heap size:   {heap_bytes} bytes (spilled: {spilled})");
+    println!("  Vec<u64>:    {} bytes inline", size_of::<Vec<Element>>());
+    println!();
+
+    let push = time(iterations, || {
+        let mut lsv: LocalStorageVec<Element, N> = LocalStorageVec::new();
+        source.iter().for_each(|&x| lsv.push(x));
+        lsv
+    });
+
+    let extend = time(iterations, || {
+        let mut lsv: LocalStorageVec<Element, N> = LocalStorageVec::new();
+        lsv.extend_from_slice(&source);
+        lsv
+    });
+
+    let vec_push = time(iterations, || {
+        let mut vec = Vec::new();
+        source.iter().for_each(|&x| vec.push(x));
+        vec
+    });
+
+    // fill the inline buffer, then push the element that moves everything to the heap
+    let spill = time(iterations, || {
+        let mut lsv: LocalStorageVec<Element, N> = LocalStorageVec::new();
+        lsv.extend((0..N as Element).map(black_box));
+        lsv.push(0);
+        lsv
+    });
+
+    println!("{:<28}{:>12}", "scenario", "per iteration");
+    for (name, duration) in [
+        ("push (one at a time)", push),
+        ("extend_from_slice", extend),
+        ("Vec::push (reference)", vec_push),
+        ("fill inline, then spill", spill),
+    ] {
+        println!("{name:<28}{:>12?}", duration / iterations.max(1) as u32);
+    }
+}
+
+/// The total time of running `f` `iterations` times
+fn time<R>(iterations: usize, mut f: impl FnMut() -> R) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        black_box(f());
+    }
+
+    start.elapsed()
+}
@@ -0,0 +1,605 @@
+use std::borrow::Borrow;
+use std::collections::{hash_map, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::LocalStorageVec;
+
+/// A map that stores up to `N` key-value pairs inline, and moves them into a `HashMap` when it
+/// grows beyond that.
+///
+/// Inline lookups are a linear scan, which beats hashing for the handful of entries this is
+/// meant for.
+#[derive(Clone)]
+pub struct LocalStorageMap<K, V, const N: usize> {
+    repr: Repr<K, V, N>,
+}
+
+#[derive(Clone)]
+enum Repr<K, V, const N: usize> {
+    /// invariant: never holds more than `N` pairs, and the keys are unique
+    Inline(LocalStorageVec<(K, V), N>),
+    Heap(HashMap<K, V>),
+}
+
+impl<K: Default, V: Default, const N: usize> LocalStorageMap<K, V, N> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline(LocalStorageVec::new()),
+        }
+    }
+}
+
+impl<K: Default, V: Default, const N: usize> Default for LocalStorageMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash, V, const N: usize> LocalStorageMap<K, V, N> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match &self.repr {
+            Repr::Inline(pairs) => pairs
+                .iter()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, v)| v),
+            Repr::Heap(map) => map.get(key),
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match &mut self.repr {
+            Repr::Inline(pairs) => pairs
+                .iter_mut()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, v)| v),
+            Repr::Heap(map) => map.get_mut(key),
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Eq + Hash + Default, V: Default, const N: usize> LocalStorageMap<K, V, N> {
+    /// Inserts a key-value pair, returning the old value if the key was already present
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+
+        match &mut self.repr {
+            Repr::Inline(pairs) if pairs.len() < N => pairs.push((key, value)),
+            Repr::Inline(_) => {
+                self.spill().insert(key, value);
+            }
+            Repr::Heap(map) => {
+                map.insert(key, value);
+            }
+        }
+
+        None
+    }
+
+    /// The entry for `key`, for in-place manipulation
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, N> {
+        let position = match &self.repr {
+            Repr::Inline(pairs) => pairs.iter().position(|(k, _)| *k == key),
+            Repr::Heap(_) => None,
+        };
+
+        if let (Repr::Inline(_), None) = (&self.repr, position) {
+            return Entry::Vacant(VacantEntry {
+                inner: VacantInner::Inline { map: self, key },
+            });
+        }
+
+        match &mut self.repr {
+            Repr::Inline(pairs) => Entry::Occupied(OccupiedEntry {
+                inner: OccupiedInner::Inline {
+                    pairs,
+                    index: position.unwrap(),
+                },
+            }),
+            Repr::Heap(map) => match map.entry(key) {
+                hash_map::Entry::Occupied(e) => Entry::Occupied(OccupiedEntry {
+                    inner: OccupiedInner::Heap(e),
+                }),
+                hash_map::Entry::Vacant(e) => Entry::Vacant(VacantEntry {
+                    inner: VacantInner::Heap(e),
+                }),
+            },
+        }
+    }
+
+    /// Move the inline pairs into a `HashMap`, and return that map
+    fn spill(&mut self) -> &mut HashMap<K, V> {
+        if let Repr::Inline(pairs) = &mut self.repr {
+            let mut map = HashMap::with_capacity(2 * N.max(1));
+            map.extend(std::mem::take(pairs));
+
+            self.repr = Repr::Heap(map);
+        }
+
+        match &mut self.repr {
+            Repr::Heap(map) => map,
+            Repr::Inline(_) => unreachable!(),
+        }
+    }
+
+    /// Removes a key, returning its value if it was present
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match &mut self.repr {
+            Repr::Inline(pairs) => {
+                let index = pairs.iter().position(|(k, _)| k.borrow() == key)?;
+                Some(pairs.remove(index).1)
+            }
+            Repr::Heap(map) => map.remove(key),
+        }
+    }
+}
+
+impl<K, V, const N: usize> LocalStorageMap<K, V, N> {
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(pairs) => pairs.len(),
+            Repr::Heap(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// An iterator over the key-value pairs. Inline pairs are visited in insertion order, spilled
+    /// pairs in `HashMap` order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        match &self.repr {
+            Repr::Inline(pairs) => Iter::Inline(pairs.iter()),
+            Repr::Heap(map) => Iter::Heap(map.iter()),
+        }
+    }
+
+    /// An iterator over the key-value pairs, with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        match &mut self.repr {
+            Repr::Inline(pairs) => IterMut::Inline(pairs.iter_mut()),
+            Repr::Heap(map) => IterMut::Heap(map.iter_mut()),
+        }
+    }
+
+    pub fn keys(&self) -> impl ExactSizeIterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl ExactSizeIterator<Item = &mut V> {
+        self.iter_mut().map(|(_, v)| v)
+    }
+}
+
+impl<K: Default, V: Default, const N: usize> LocalStorageMap<K, V, N> {
+    /// Keeps only the pairs for which `f` returns `true`
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        match &mut self.repr {
+            Repr::Inline(pairs) => pairs.retain_mut(|(k, v)| f(k, v)),
+            Repr::Heap(map) => map.retain(f),
+        }
+    }
+}
+
+/// Only the live entries are shown, not whether they are inline or spilled
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for LocalStorageMap<K, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the key-value pairs of a [`LocalStorageMap`]
+pub enum Iter<'a, K, V> {
+    Inline(std::slice::Iter<'a, (K, V)>),
+    Heap(hash_map::Iter<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Inline(it) => it.next().map(|(k, v)| (k, v)),
+            Iter::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Inline(it) => it.size_hint(),
+            Iter::Heap(it) => it.size_hint(),
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Iterator over the key-value pairs of a [`LocalStorageMap`], with mutable values
+pub enum IterMut<'a, K, V> {
+    Inline(std::slice::IterMut<'a, (K, V)>),
+    Heap(hash_map::IterMut<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Inline(it) => it.next().map(|(k, v)| (&*k, v)),
+            IterMut::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IterMut::Inline(it) => it.size_hint(),
+            IterMut::Heap(it) => it.size_hint(),
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Owned iterator over the key-value pairs of a [`LocalStorageMap`]
+pub enum IntoIter<K, V, const N: usize> {
+    Inline(crate::IntoIter<(K, V), N>),
+    Heap(hash_map::IntoIter<K, V>),
+}
+
+impl<K, V, const N: usize> Iterator for IntoIter<K, V, N> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Inline(it) => it.next(),
+            IntoIter::Heap(it) => it.next(),
+        }
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for LocalStorageMap<K, V, N> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.repr {
+            Repr::Inline(pairs) => IntoIter::Inline(pairs.into_iter()),
+            Repr::Heap(map) => IntoIter::Heap(map.into_iter()),
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a LocalStorageMap<K, V, N> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut LocalStorageMap<K, V, N> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A view into a single entry of a [`LocalStorageMap`], see [`LocalStorageMap::entry`]
+pub enum Entry<'a, K, V, const N: usize> {
+    Occupied(OccupiedEntry<'a, K, V, N>),
+    Vacant(VacantEntry<'a, K, V, N>),
+}
+
+/// An entry whose key is present in the map
+pub struct OccupiedEntry<'a, K, V, const N: usize> {
+    inner: OccupiedInner<'a, K, V, N>,
+}
+
+enum OccupiedInner<'a, K, V, const N: usize> {
+    Inline {
+        pairs: &'a mut LocalStorageVec<(K, V), N>,
+        index: usize,
+    },
+    Heap(hash_map::OccupiedEntry<'a, K, V>),
+}
+
+/// An entry whose key is not present in the map
+pub struct VacantEntry<'a, K, V, const N: usize> {
+    inner: VacantInner<'a, K, V, N>,
+}
+
+enum VacantInner<'a, K, V, const N: usize> {
+    // inserting may need to spill, so this borrows the whole map
+    Inline {
+        map: &'a mut LocalStorageMap<K, V, N>,
+        key: K,
+    },
+    Heap(hash_map::VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: Eq + Hash + Default, V: Default, const N: usize> Entry<'a, K, V, N> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(e.key());
+                e.insert(value)
+            }
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+
+    /// Modify the value in place if the entry is occupied
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+
+        self
+    }
+}
+
+impl<'a, K, V, const N: usize> OccupiedEntry<'a, K, V, N> {
+    pub fn key(&self) -> &K {
+        match &self.inner {
+            OccupiedInner::Inline { pairs, index } => &pairs[*index].0,
+            OccupiedInner::Heap(e) => e.key(),
+        }
+    }
+
+    pub fn get(&self) -> &V {
+        match &self.inner {
+            OccupiedInner::Inline { pairs, index } => &pairs[*index].1,
+            OccupiedInner::Heap(e) => e.get(),
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        match &mut self.inner {
+            OccupiedInner::Inline { pairs, index } => &mut pairs[*index].1,
+            OccupiedInner::Heap(e) => e.get_mut(),
+        }
+    }
+
+    /// Convert into a mutable reference to the value, with the lifetime of the map borrow
+    pub fn into_mut(self) -> &'a mut V {
+        match self.inner {
+            OccupiedInner::Inline { pairs, index } => &mut pairs[index].1,
+            OccupiedInner::Heap(e) => e.into_mut(),
+        }
+    }
+
+    /// Replace the value, returning the old one
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'a, K: Default, V: Default, const N: usize> OccupiedEntry<'a, K, V, N> {
+    /// Remove the entry from the map, returning the value
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Remove the entry from the map, returning the key and value
+    pub fn remove_entry(self) -> (K, V) {
+        match self.inner {
+            OccupiedInner::Inline { pairs, index } => pairs.remove(index),
+            OccupiedInner::Heap(e) => e.remove_entry(),
+        }
+    }
+}
+
+impl<'a, K: Eq + Hash + Default, V: Default, const N: usize> VacantEntry<'a, K, V, N> {
+    pub fn key(&self) -> &K {
+        match &self.inner {
+            VacantInner::Inline { key, .. } => key,
+            VacantInner::Heap(e) => e.key(),
+        }
+    }
+
+    pub fn into_key(self) -> K {
+        match self.inner {
+            VacantInner::Inline { key, .. } => key,
+            VacantInner::Heap(e) => e.into_key(),
+        }
+    }
+
+    /// Insert `value` for this entry's key, spilling to the heap if the map is full inline
+    pub fn insert(self, value: V) -> &'a mut V {
+        match self.inner {
+            VacantInner::Inline { map, key } => {
+                if map.len() >= N {
+                    return map.spill().entry(key).or_insert(value);
+                }
+
+                match &mut map.repr {
+                    Repr::Inline(pairs) => {
+                        pairs.push((key, value));
+                        &mut pairs.last_mut().unwrap().1
+                    }
+                    Repr::Heap(_) => unreachable!(),
+                }
+            }
+            VacantInner::Heap(e) => e.insert(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map: LocalStorageMap<&str, u32, 2> = LocalStorageMap::new();
+
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("b", 2), None);
+        assert_eq!(map.insert("a", 10), Some(1));
+        assert_eq!(map.len(), 2);
+        assert!(matches!(map.repr, Repr::Inline(_)));
+
+        *map.get_mut("b").unwrap() += 1;
+        assert_eq!(map.get("b"), Some(&3));
+        assert!(map.contains_key("a"));
+
+        assert_eq!(map.remove("a"), Some(10));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn entry_counting() {
+        let mut counts: LocalStorageMap<char, u32, 2> = LocalStorageMap::new();
+
+        for c in "abacab".chars() {
+            *counts.entry(c).or_default() += 1;
+        }
+
+        assert!(matches!(counts.repr, Repr::Heap(_)));
+        assert_eq!(counts.get(&'a'), Some(&3));
+        assert_eq!(counts.get(&'b'), Some(&2));
+        assert_eq!(counts.get(&'c'), Some(&1));
+    }
+
+    #[test]
+    fn entry_inline() {
+        let mut map: LocalStorageMap<&str, u32, 4> = LocalStorageMap::new();
+
+        map.entry("a").and_modify(|v| *v += 1).or_insert(10);
+        map.entry("a").and_modify(|v| *v += 1).or_insert(10);
+        assert_eq!(map.get("a"), Some(&11));
+
+        assert_eq!(*map.entry("b").or_insert_with_key(|k| k.len() as u32), 1);
+
+        match map.entry("a") {
+            Entry::Occupied(e) => assert_eq!(e.remove_entry(), ("a", 11)),
+            Entry::Vacant(_) => unreachable!(),
+        }
+
+        assert!(!map.contains_key("a"));
+        assert!(matches!(map.repr, Repr::Inline(_)));
+    }
+
+    #[test]
+    fn iteration() {
+        let mut map: LocalStorageMap<&str, u32, 4> = LocalStorageMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"a", &1), (&"b", &2)]);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "b"]);
+
+        for v in map.values_mut() {
+            *v *= 10;
+        }
+        for (_, v) in &mut map {
+            *v += 1;
+        }
+
+        assert_eq!(map.values().sum::<u32>(), 32);
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), [("a", 11), ("b", 21)]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut map: LocalStorageMap<u32, u32, 2> = LocalStorageMap::new();
+        map.insert(1, 1);
+        map.insert(2, 2);
+
+        map.retain(|k, _| *k != 1);
+        assert_eq!(map.len(), 1);
+
+        map.insert(3, 3);
+        map.insert(4, 4);
+        assert!(matches!(map.repr, Repr::Heap(_)));
+
+        map.retain(|_, v| {
+            *v += 1;
+            *v % 2 == 0
+        });
+
+        let mut pairs: Vec<_> = map.into_iter().collect();
+        pairs.sort();
+        assert_eq!(pairs, [(3, 4)]);
+    }
+
+    #[test]
+    fn spills_to_hash_map() {
+        let mut map: LocalStorageMap<String, u32, 2> = LocalStorageMap::new();
+
+        for (i, key) in ["x", "y", "z"].into_iter().enumerate() {
+            map.insert(key.to_string(), i as u32);
+        }
+
+        assert!(matches!(map.repr, Repr::Heap(_)));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("x"), Some(&0));
+        assert_eq!(map.get("z"), Some(&2));
+        assert_eq!(map.remove("y"), Some(1));
+    }
+
+    #[test]
+    fn debug_shows_only_entries() {
+        let mut map: LocalStorageMap<&str, u32, 4> = LocalStorageMap::new();
+        map.insert("a", 1);
+        assert_eq!(format!("{map:?}"), r#"{"a": 1}"#);
+
+        let mut map: LocalStorageMap<&str, u32, 0> = LocalStorageMap::new();
+        map.insert("a", 1);
+        assert!(matches!(map.repr, Repr::Heap(_)));
+        assert_eq!(format!("{map:?}"), r#"{"a": 1}"#);
+    }
+}
@@ -0,0 +1,92 @@
+use std::iter::Sum;
+use std::mem::size_of;
+use std::ops::{Add, AddAssign};
+
+use crate::LocalStorageVec;
+
+/// The memory used by a vector, see [`LocalStorageVec::memory_usage`]. Sums up, so the usage of
+/// many vectors can be aggregated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// the size of the vector itself, including the inline buffer
+    pub inline_bytes: usize,
+    /// the size of the heap allocation, 0 for an inline vector
+    pub heap_bytes: usize,
+    /// the size of the unused capacity (inline or on the heap)
+    pub wasted_bytes: usize,
+}
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// The bytes used inline, allocated on the heap, and taken up by unused capacity
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let heap_bytes = match self {
+            LocalStorageVec::Stack { .. } => 0,
+            LocalStorageVec::Heap(v) => v.capacity() * size_of::<T>(),
+        };
+
+        MemoryUsage {
+            inline_bytes: size_of::<Self>(),
+            heap_bytes,
+            wasted_bytes: self.remaining_capacity() * size_of::<T>(),
+        }
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = MemoryUsage;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.inline_bytes += rhs.inline_bytes;
+        self.heap_bytes += rhs.heap_bytes;
+        self.wasted_bytes += rhs.wasted_bytes;
+    }
+}
+
+impl Sum for MemoryUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(MemoryUsage::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn memory_usage() {
+        let inline: LocalStorageVec<u32, 4> = LocalStorageVec::from([1, 2, 3]);
+        let inline_bytes = size_of::<LocalStorageVec<u32, 4>>();
+
+        assert_eq!(
+            inline.memory_usage(),
+            MemoryUsage {
+                inline_bytes,
+                heap_bytes: 0,
+                wasted_bytes: 4,
+            }
+        );
+
+        let mut heap: LocalStorageVec<u32, 4> = LocalStorageVec::Heap(Vec::with_capacity(10));
+        heap.extend([1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(
+            heap.memory_usage(),
+            MemoryUsage {
+                inline_bytes,
+                heap_bytes: 40,
+                wasted_bytes: 16,
+            }
+        );
+
+        let total: MemoryUsage = [&inline, &heap].iter().map(|v| v.memory_usage()).sum();
+        assert_eq!(total.inline_bytes, 2 * inline_bytes);
+        assert_eq!((total.heap_bytes, total.wasted_bytes), (40, 20));
+    }
+}
@@ -0,0 +1,97 @@
+//! Process-wide counters, enabled by the `metrics` feature. Without it, recording compiles to
+//! nothing.
+
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "metrics")]
+static SPILLS: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "metrics")]
+static HEAP_REALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "metrics")]
+static DEMOTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A snapshot of the counters, see [`stats`]
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// vectors that moved from the inline buffer to the heap
+    pub spills: u64,
+    /// heap buffers that were grown by `push` or `reserve`
+    pub heap_reallocations: u64,
+    /// vectors that moved from the heap back into the inline buffer
+    pub demotions: u64,
+}
+
+/// The counters of all `LocalStorageVec`s since the process started
+#[cfg(feature = "metrics")]
+pub fn stats() -> Stats {
+    Stats {
+        spills: SPILLS.load(Ordering::Relaxed),
+        heap_reallocations: HEAP_REALLOCATIONS.load(Ordering::Relaxed),
+        demotions: DEMOTIONS.load(Ordering::Relaxed),
+    }
+}
+
+#[inline]
+pub(crate) fn record_spill() {
+    #[cfg(feature = "metrics")]
+    SPILLS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline]
+pub(crate) fn record_demotion() {
+    #[cfg(feature = "metrics")]
+    DEMOTIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record a reallocation if a heap buffer's capacity changed from `old` to `new`
+#[inline]
+pub(crate) fn record_growth(old: usize, new: usize) {
+    #[cfg(feature = "metrics")]
+    if old != new {
+        HEAP_REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = (old, new);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod test {
+    use super::*;
+    use crate::LocalStorageVec;
+
+    #[test]
+    fn counters() {
+        // other tests run concurrently, so only check that the counters went up
+        let before = stats();
+
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::new();
+        lsv.extend([1, 2, 3]);
+        lsv.reserve(100);
+        lsv.truncate(2);
+        lsv.grow(2);
+
+        let after = stats();
+        assert!(after.spills > before.spills);
+        assert!(after.heap_reallocations > before.heap_reallocations);
+        assert!(after.demotions > before.demotions);
+    }
+
+    #[test]
+    fn try_grow_counts_like_grow() {
+        let mut lsv: LocalStorageVec<u8, 2> = LocalStorageVec::Heap(Vec::with_capacity(4));
+
+        let before = stats();
+        lsv.try_grow(64).unwrap();
+        let grown = stats();
+        lsv.try_grow(8).unwrap();
+        let shrunk = stats();
+
+        assert!(grown.heap_reallocations > before.heap_reallocations);
+        assert!(shrunk.heap_reallocations > grown.heap_reallocations);
+    }
+}
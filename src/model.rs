@@ -0,0 +1,177 @@
+//! Model-based tests: random operation sequences are applied to both a `LocalStorageVec` and a
+//! `Vec`, which must agree after every step.
+//!
+//! The sequences come from a small seeded PRNG, and a failure reports its seed, so it can be
+//! replayed with `replay(seed)`.
+
+use crate::LocalStorageVec;
+
+/// xorshift64*, plenty for generating operation sequences
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck on 0
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A value in `0..=max`
+    fn up_to(&mut self, max: usize) -> usize {
+        (self.next() % (max as u64 + 1)) as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Push(u32),
+    Pop,
+    Insert(usize, u32),
+    Remove(usize),
+    Truncate(usize),
+    Clear,
+    Reserve(usize),
+    ExtendFromSlice(Vec<u32>),
+    Extend(Vec<u32>),
+    Resize(usize, u32),
+    RetainEven,
+    Drain(usize, usize),
+    SortStable,
+    IntoIterRoundTrip,
+}
+
+impl Op {
+    /// A random operation; indices are valid for a vector of length `len`
+    fn generate(rng: &mut Rng, len: usize) -> Op {
+        let value = rng.next() as u32 % 100;
+        let values = |rng: &mut Rng| (0..rng.up_to(6)).map(|i| value + i as u32).collect();
+
+        match rng.up_to(14) {
+            0 | 1 => Op::Push(value),
+            2 => Op::Pop,
+            3 => Op::Insert(rng.up_to(len), value),
+            4 if len > 0 => Op::Remove(rng.up_to(len - 1)),
+            5 => Op::Truncate(rng.up_to(len + 1)),
+            // clearing often would keep the vectors from growing past `N`
+            6 if rng.up_to(4) == 0 => Op::Clear,
+            7 => Op::Reserve(rng.up_to(8)),
+            8 => Op::ExtendFromSlice(values(rng)),
+            9 => Op::Extend(values(rng)),
+            10 => Op::Resize(rng.up_to(len + 4), value),
+            11 => Op::RetainEven,
+            12 => {
+                let (a, b) = (rng.up_to(len), rng.up_to(len));
+                Op::Drain(a.min(b), a.max(b))
+            }
+            13 => Op::SortStable,
+            _ => Op::IntoIterRoundTrip,
+        }
+    }
+}
+
+/// Apply `op` to both vectors, returning whether it required capacity for more than `N`
+/// elements (the only reason to spill)
+fn apply<const N: usize>(op: &Op, lsv: &mut LocalStorageVec<u32, N>, vec: &mut Vec<u32>) -> bool {
+    let len = vec.len();
+
+    match op.clone() {
+        Op::Push(value) => {
+            lsv.push(value);
+            vec.push(value);
+        }
+        Op::Pop => assert_eq!(lsv.pop(), vec.pop()),
+        Op::Insert(index, value) => {
+            lsv.insert(index, value);
+            vec.insert(index, value);
+        }
+        Op::Remove(index) => assert_eq!(lsv.remove(index), vec.remove(index)),
+        Op::Truncate(new_len) => {
+            lsv.truncate(new_len);
+            vec.truncate(new_len);
+        }
+        Op::Clear => {
+            lsv.clear();
+            vec.clear();
+        }
+        Op::Reserve(additional) => {
+            lsv.reserve(additional);
+            vec.reserve(additional);
+            assert!(lsv.capacity() >= len + additional);
+            return len + additional > N;
+        }
+        Op::ExtendFromSlice(values) => {
+            lsv.extend_from_slice(&values);
+            vec.extend_from_slice(&values);
+        }
+        Op::Extend(values) => {
+            lsv.extend(values.iter().copied());
+            vec.extend(values);
+        }
+        Op::Resize(new_len, value) => {
+            lsv.resize(new_len, value);
+            vec.resize(new_len, value);
+        }
+        Op::RetainEven => {
+            lsv.retain(|x| x % 2 == 0);
+            vec.retain(|x| x % 2 == 0);
+        }
+        Op::Drain(start, end) => assert!(lsv.drain(start..end).eq(vec.drain(start..end))),
+        Op::SortStable => {
+            lsv.sort_stable_inline();
+            vec.sort();
+        }
+        Op::IntoIterRoundTrip => {
+            let mut rebuilt = LocalStorageVec::new();
+            rebuilt.extend(std::mem::take(lsv));
+            *lsv = rebuilt;
+        }
+    }
+
+    vec.len() > N
+}
+
+/// Run the operation sequence generated from `seed`, panicking with the seed and the failing
+/// step if the vectors disagree
+fn replay<const N: usize>(seed: u64, steps: usize) {
+    let mut rng = Rng::new(seed);
+    let mut lsv: LocalStorageVec<u32, N> = LocalStorageVec::new();
+    let mut vec = Vec::new();
+
+    // whether an operation so far needed more than `N` elements of capacity
+    let mut forced = false;
+
+    for step in 0..steps {
+        let op = Op::generate(&mut rng, vec.len());
+        let context = || format!("seed {seed}, step {step}: {op:?}");
+
+        forced |= apply(&op, &mut lsv, &mut vec);
+
+        lsv.check_invariants();
+        assert_eq!(&lsv[..], &vec[..], "{}", context());
+        assert!(lsv.capacity() >= lsv.len(), "{}", context());
+
+        let spilled = matches!(lsv, LocalStorageVec::Heap(_));
+        assert!(!spilled || forced, "spilled while len <= N, {}", context());
+
+        // `IntoIterRoundTrip` starts over with a fresh inline vector
+        if !spilled {
+            forced = false;
+        }
+    }
+}
+
+#[test]
+fn agrees_with_vec() {
+    for seed in 0..300 {
+        replay::<0>(seed, 100);
+        replay::<1>(seed, 100);
+        replay::<4>(seed, 100);
+        replay::<16>(seed, 100);
+    }
+}
@@ -0,0 +1,137 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::LocalStorageVec;
+
+thread_local! {
+    /// Spilled scratch buffers, keyed by the `TypeId` of their `LocalStorageVec<T, N>`
+    static POOL: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Pooled buffers are shrunk to at most this many bytes, so one large use doesn't pin its memory
+/// for the lifetime of the thread
+const MAX_RETAINED_BYTES: usize = 64 * 1024;
+
+/// Runs `f` with an empty scratch buffer.
+///
+/// A buffer that spilled during `f` is cleared and kept in a thread-local pool afterwards (shrunk
+/// to at most 64 KiB), so the next call on this thread with the same `T` and `N` reuses its heap
+/// allocation. Nested calls each get their own buffer. While the thread is being torn down the
+/// pool is unavailable, and `f` gets a fresh buffer.
+pub fn with_scratch<T: Default + 'static, const N: usize, R>(
+    f: impl FnOnce(&mut LocalStorageVec<T, N>) -> R,
+) -> R {
+    let key = TypeId::of::<LocalStorageVec<T, N>>();
+
+    // the box itself is reused too, so a pooled buffer costs no allocation at all
+    let pooled = POOL
+        .try_with(|pool| pool.borrow_mut().remove(&key))
+        .ok()
+        .flatten()
+        .and_then(|boxed| boxed.downcast::<LocalStorageVec<T, N>>().ok());
+    let mut boxed = pooled.unwrap_or_else(|| Box::new(LocalStorageVec::new()));
+
+    let result = f(&mut boxed);
+
+    // an inline buffer has no allocation worth keeping
+    if let LocalStorageVec::Heap(v) = &mut *boxed {
+        v.clear();
+
+        let max_capacity = MAX_RETAINED_BYTES / std::mem::size_of::<T>().max(1);
+        v.shrink_to(max_capacity.max(N));
+
+        // if the pool is gone, the buffer is simply dropped
+        let _ = POOL.try_with(|pool| pool.borrow_mut().insert(key, boxed));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spilled_buffer_is_reused() {
+        let capacity = with_scratch(|buf: &mut LocalStorageVec<u16, 2>| {
+            assert!(buf.is_empty());
+            buf.extend(0..10);
+            buf.capacity()
+        });
+
+        with_scratch(|buf: &mut LocalStorageVec<u16, 2>| {
+            assert!(buf.is_empty());
+            assert_eq!(buf.capacity(), capacity);
+        });
+    }
+
+    #[test]
+    fn pooled_box_is_reused() {
+        let first = with_scratch(|buf: &mut LocalStorageVec<u32, 1>| {
+            buf.extend([1, 2, 3]);
+            buf as *const LocalStorageVec<u32, 1>
+        });
+
+        let second =
+            with_scratch(|buf: &mut LocalStorageVec<u32, 1>| buf as *const LocalStorageVec<u32, 1>);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn large_buffers_are_shrunk() {
+        with_scratch(|buf: &mut LocalStorageVec<u64, 4>| {
+            buf.extend(0..100_000);
+        });
+
+        with_scratch(|buf: &mut LocalStorageVec<u64, 4>| {
+            assert!(buf.is_empty());
+            assert!(buf.capacity() <= MAX_RETAINED_BYTES / 8);
+            assert!(buf.capacity() > 4);
+        });
+    }
+
+    #[test]
+    fn usable_from_thread_local_destructor() {
+        struct UsesScratch;
+
+        impl Drop for UsesScratch {
+            fn drop(&mut self) {
+                let sum = with_scratch(|buf: &mut LocalStorageVec<u8, 2>| {
+                    buf.extend([1, 2, 3]);
+                    buf.iter().sum::<u8>()
+                });
+                assert_eq!(sum, 6);
+            }
+        }
+
+        thread_local! {
+            static LATE: RefCell<Option<UsesScratch>> = const { RefCell::new(None) };
+        }
+
+        std::thread::spawn(|| {
+            // thread locals are destroyed in reverse order of first use, so the pool (used
+            // second) is already gone when `LATE`'s value is dropped
+            LATE.with(|late| *late.borrow_mut() = Some(UsesScratch));
+            with_scratch(|buf: &mut LocalStorageVec<u8, 2>| buf.extend([0; 4]));
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn nested_calls_get_distinct_buffers() {
+        let sum = with_scratch(|outer: &mut LocalStorageVec<u32, 4>| {
+            outer.extend([1, 2, 3]);
+
+            with_scratch(|inner: &mut LocalStorageVec<u32, 4>| {
+                assert!(inner.is_empty());
+                inner.extend(outer.iter().map(|x| x * 10));
+                inner.iter().sum::<u32>()
+            })
+        });
+
+        assert_eq!(sum, 60);
+    }
+}
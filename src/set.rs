@@ -0,0 +1,358 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::{hash_set, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use crate::LocalStorageVec;
+
+/// A set that stores up to `N` values inline, and moves them into a `HashSet` when it grows
+/// beyond that.
+///
+/// Inline membership tests are a linear scan. The hasher `S` is only used once the set has
+/// spilled, so e.g. an fxhash `BuildHasher` can be plugged in for speed, or a keyed one for
+/// resistance against collision attacks.
+#[derive(Clone)]
+pub struct LocalStorageSet<T, const N: usize, S = RandomState> {
+    repr: Repr<T, N, S>,
+}
+
+#[derive(Clone)]
+enum Repr<T, const N: usize, S> {
+    /// invariant: never holds more than `N` values, and the values are unique
+    Inline(LocalStorageVec<T, N>, S),
+    Heap(HashSet<T, S>),
+}
+
+impl<T: Default, const N: usize> LocalStorageSet<T, N> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<T: Default, const N: usize, S> LocalStorageSet<T, N, S> {
+    /// An empty set that will use `hasher` once it spills
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            repr: Repr::Inline(LocalStorageVec::new(), hasher),
+        }
+    }
+}
+
+impl<T: Default, const N: usize, S: Default> Default for LocalStorageSet<T, N, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T, const N: usize, S> LocalStorageSet<T, N, S> {
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(values, _) => values.len(),
+            Repr::Heap(set) => set.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// An iterator over the values. Inline values are visited in insertion order, spilled values
+    /// in `HashSet` order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        match &self.repr {
+            Repr::Inline(values, _) => Iter::Inline(values.iter()),
+            Repr::Heap(set) => Iter::Heap(set.iter()),
+        }
+    }
+
+    /// The hasher used by the set once it has spilled
+    pub fn hasher(&self) -> &S {
+        match &self.repr {
+            Repr::Inline(_, hasher) => hasher,
+            Repr::Heap(set) => set.hasher(),
+        }
+    }
+}
+
+impl<T: Eq + Hash, const N: usize, S: BuildHasher> LocalStorageSet<T, N, S> {
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match &self.repr {
+            Repr::Inline(values, _) => values.iter().any(|v| v.borrow() == value),
+            Repr::Heap(set) => set.contains(value),
+        }
+    }
+
+    /// The values in `self` or `other`, without duplicates
+    pub fn union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().chain(other.difference(self))
+    }
+
+    /// The values in both `self` and `other`
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        // scan the smaller set, probe the larger one
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        small.iter().filter(move |v| large.contains(*v))
+    }
+
+    /// The values in `self` but not in `other`
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().filter(move |v| !other.contains(*v))
+    }
+
+    /// The values in `self` or `other`, but not in both
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.difference(other).chain(other.difference(self))
+    }
+
+    /// Whether `self` and `other` have no values in common
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    /// Whether all values of `self` are also in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+
+    /// Whether all values of `other` are also in `self`
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+}
+
+impl<T: Eq + Hash + Default, const N: usize, S: BuildHasher + Clone> LocalStorageSet<T, N, S> {
+    /// Adds a value, returning whether it was newly inserted
+    pub fn insert(&mut self, value: T) -> bool {
+        match &mut self.repr {
+            Repr::Inline(values, _) if values.contains(&value) => false,
+            Repr::Inline(values, _) if values.len() < N => {
+                values.push(value);
+                true
+            }
+            Repr::Inline(values, hasher) => {
+                let mut set = HashSet::with_capacity_and_hasher(2 * N.max(1), hasher.clone());
+                set.extend(std::mem::take(values));
+                set.insert(value);
+
+                self.repr = Repr::Heap(set);
+                true
+            }
+            Repr::Heap(set) => set.insert(value),
+        }
+    }
+
+    /// Removes a value, returning whether it was present
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match &mut self.repr {
+            Repr::Inline(values, _) => match values.iter().position(|v| v.borrow() == value) {
+                Some(index) => {
+                    values.remove(index);
+                    true
+                }
+                None => false,
+            },
+            Repr::Heap(set) => set.remove(value),
+        }
+    }
+}
+
+impl<T: Eq + Hash + Default, const N: usize, S: BuildHasher + Clone> Extend<T>
+    for LocalStorageSet<T, N, S>
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// Duplicate values are only inserted once
+impl<T: Eq + Hash + Default, const N: usize, S: BuildHasher + Clone + Default> FromIterator<T>
+    for LocalStorageSet<T, N, S>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Only the live values are shown, not whether they are inline or spilled, nor the hasher
+impl<T: fmt::Debug, const N: usize, S> fmt::Debug for LocalStorageSet<T, N, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the values of a [`LocalStorageSet`]
+pub enum Iter<'a, T> {
+    Inline(std::slice::Iter<'a, T>),
+    Heap(hash_set::Iter<'a, T>),
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Inline(it) => it.next(),
+            Iter::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Inline(it) => it.size_hint(),
+            Iter::Heap(it) => it.size_hint(),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T, const N: usize, S> IntoIterator for &'a LocalStorageSet<T, N, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_contains_remove() {
+        let mut set: LocalStorageSet<&str, 2> = LocalStorageSet::new();
+
+        assert!(set.insert("a"));
+        assert!(!set.insert("a"));
+        assert!(set.insert("b"));
+        assert!(matches!(set.repr, Repr::Inline(..)));
+
+        assert!(set.contains("a"));
+        assert!(set.remove("a"));
+        assert!(!set.remove("a"));
+        assert!(!set.contains("a"));
+        assert_eq!(set.len(), 1);
+    }
+
+    fn sorted<'a>(it: impl Iterator<Item = &'a u8>) -> Vec<u8> {
+        let mut v: Vec<_> = it.copied().collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: LocalStorageSet<u8, 4> = [1, 2, 3].into_iter().collect();
+        let b: LocalStorageSet<u8, 4> = [2, 3, 4, 5, 6].into_iter().collect();
+
+        assert_eq!(sorted(a.union(&b)), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(sorted(a.intersection(&b)), [2, 3]);
+        assert_eq!(sorted(b.intersection(&a)), [2, 3]);
+        assert_eq!(sorted(a.difference(&b)), [1]);
+        assert_eq!(sorted(a.symmetric_difference(&b)), [1, 4, 5, 6]);
+    }
+
+    #[test]
+    fn subset_and_disjoint() {
+        let a: LocalStorageSet<u8, 4> = [1, 2].into_iter().collect();
+        let b: LocalStorageSet<u8, 4> = [1, 2, 3].into_iter().collect();
+        let c: LocalStorageSet<u8, 4> = [4].into_iter().collect();
+
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
+        assert!(a.is_disjoint(&c));
+        assert!(!a.is_disjoint(&b));
+    }
+
+    #[test]
+    fn from_iter_deduplicates_and_spills() {
+        let set: LocalStorageSet<u8, 2> = [1, 2, 1, 3, 2].into_iter().collect();
+
+        assert_eq!(set.len(), 3);
+        assert!(matches!(set.repr, Repr::Heap(_)));
+        assert!([1, 2, 3].iter().all(|v| set.contains(v)));
+
+        let set: LocalStorageSet<u8, 4> = [1, 1, 1].into_iter().collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), [&1]);
+    }
+
+    #[test]
+    fn debug_shows_only_values() {
+        let set: LocalStorageSet<u8, 4> = [1].into_iter().collect();
+        assert_eq!(format!("{set:?}"), "{1}");
+
+        let set: LocalStorageSet<u8, 0> = [1].into_iter().collect();
+        assert!(matches!(set.repr, Repr::Heap(_)));
+        assert_eq!(format!("{set:?}"), "{1}");
+    }
+
+    #[test]
+    fn custom_hasher_is_used_after_spilling() {
+        use std::cell::Cell;
+        use std::collections::hash_map::DefaultHasher;
+        use std::rc::Rc;
+
+        /// Counts the hashers it builds; clones share the count
+        #[derive(Clone)]
+        struct Counting {
+            id: u32,
+            built: Rc<Cell<usize>>,
+        }
+
+        impl BuildHasher for Counting {
+            type Hasher = DefaultHasher;
+
+            fn build_hasher(&self) -> DefaultHasher {
+                self.built.set(self.built.get() + 1);
+                DefaultHasher::new()
+            }
+        }
+
+        let built = Rc::new(Cell::new(0));
+        let hasher = Counting {
+            id: 7,
+            built: built.clone(),
+        };
+
+        let mut set: LocalStorageSet<u8, 2, Counting> = LocalStorageSet::with_hasher(hasher);
+        set.extend([1, 2, 2]);
+        assert!(set.contains(&1));
+
+        // inline lookups don't hash
+        assert!(matches!(set.repr, Repr::Inline(..)));
+        assert_eq!(built.get(), 0);
+        assert_eq!(set.hasher().id, 7);
+
+        set.insert(3);
+        assert!(matches!(set.repr, Repr::Heap(_)));
+        let after_spill = built.get();
+        assert!(after_spill >= 3, "every moved value is hashed");
+
+        assert!(set.contains(&2));
+        assert_eq!(built.get(), after_spill + 1);
+
+        // the spilled set holds (a clone of) the hasher that was passed in
+        assert_eq!(set.hasher().id, 7);
+        assert!(Rc::ptr_eq(&set.hasher().built, &built));
+    }
+}
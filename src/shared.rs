@@ -0,0 +1,164 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::LocalStorageVec;
+
+/// A vector that stores up to `N` elements inline, and spills into a reference-counted heap
+/// buffer.
+///
+/// Cloning a spilled vector only increments the reference count. The buffer is copied when a
+/// shared vector is mutated (copy-on-write), so clones that are never mutated stay cheap.
+#[derive(Debug, Clone)]
+pub struct LocalStorageSharedVec<T, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+#[derive(Debug, Clone)]
+enum Repr<T, const N: usize> {
+    /// invariant: always the `Stack` variant
+    Inline(LocalStorageVec<T, N>),
+    Shared(Arc<Vec<T>>),
+}
+
+impl<T: Default, const N: usize> LocalStorageSharedVec<T, N> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline(LocalStorageVec::new()),
+        }
+    }
+}
+
+impl<T: Default, const N: usize> Default for LocalStorageSharedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Default, const N: usize> LocalStorageSharedVec<T, N> {
+    /// Append an element, copying the heap buffer first if it is shared
+    pub fn push(&mut self, value: T) {
+        match &mut self.repr {
+            Repr::Inline(lsv) if !lsv.is_full() => lsv.push(value),
+            Repr::Inline(lsv) => {
+                lsv.push(value);
+                if let LocalStorageVec::Heap(v) = lsv.take() {
+                    self.repr = Repr::Shared(Arc::new(v));
+                }
+            }
+            Repr::Shared(shared) => Arc::make_mut(shared).push(value),
+        }
+    }
+
+    /// Remove the last element, copying the heap buffer first if it is shared.
+    ///
+    /// A spilled vector stays on the heap, even when its elements would fit inline again.
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline(lsv) => lsv.pop(),
+            Repr::Shared(shared) => Arc::make_mut(shared).pop(),
+        }
+    }
+
+    /// Mutable access to the elements, copying the heap buffer first if it is shared
+    pub fn make_mut(&mut self) -> &mut [T] {
+        match &mut self.repr {
+            Repr::Inline(lsv) => lsv,
+            Repr::Shared(shared) => Arc::make_mut(shared).as_mut_slice(),
+        }
+    }
+
+    /// The elements as a plain `LocalStorageVec`, which reuses the heap buffer if this is its
+    /// only owner
+    pub fn into_vec(self) -> LocalStorageVec<T, N> {
+        match self.repr {
+            Repr::Inline(lsv) => lsv,
+            Repr::Shared(shared) => {
+                let v = Arc::try_unwrap(shared).unwrap_or_else(|shared| (*shared).clone());
+                LocalStorageVec::Heap(v)
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> LocalStorageSharedVec<T, N> {
+    /// Whether the elements are stored inline
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline(_))
+    }
+
+    /// Whether the heap buffer is shared with a clone, so the next mutation copies it
+    pub fn is_shared(&self) -> bool {
+        match &self.repr {
+            Repr::Inline(_) => false,
+            Repr::Shared(shared) => Arc::strong_count(shared) > 1,
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for LocalStorageSharedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.repr {
+            Repr::Inline(lsv) => lsv,
+            Repr::Shared(shared) => shared,
+        }
+    }
+}
+
+impl<T, const N: usize> From<LocalStorageVec<T, N>> for LocalStorageSharedVec<T, N> {
+    fn from(lsv: LocalStorageVec<T, N>) -> Self {
+        let repr = match lsv {
+            LocalStorageVec::Heap(v) => Repr::Shared(Arc::new(v)),
+            inline => Repr::Inline(inline),
+        };
+
+        Self { repr }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inline_until_full() {
+        let mut shared: LocalStorageSharedVec<u8, 2> = LocalStorageSharedVec::new();
+        shared.push(1);
+        shared.push(2);
+        assert!(shared.is_inline());
+
+        shared.push(3);
+        assert!(!shared.is_inline());
+        assert_eq!(&shared[..], &[1, 2, 3]);
+
+        assert_eq!(shared.pop(), Some(3));
+        assert!(!shared.is_inline());
+    }
+
+    #[test]
+    fn clones_share_until_mutated() {
+        let shared: LocalStorageSharedVec<String, 1> =
+            LocalStorageVec::Heap(vec!["a".to_string(), "b".to_string()]).into();
+
+        let mut clone = shared.clone();
+        assert!(shared.is_shared() && clone.is_shared());
+        assert_eq!(shared.as_ptr(), clone.as_ptr());
+
+        clone.make_mut()[0].push('!');
+        assert!(!shared.is_shared() && !clone.is_shared());
+        assert_ne!(shared.as_ptr(), clone.as_ptr());
+        assert_eq!(&shared[..], &["a", "b"]);
+        assert_eq!(&clone[..], &["a!", "b"]);
+    }
+
+    #[test]
+    fn into_vec_reuses_unique_buffer() {
+        let shared: LocalStorageSharedVec<u8, 1> = LocalStorageVec::Heap(vec![1, 2]).into();
+        let ptr = shared.as_ptr();
+
+        let clone = shared.clone();
+        assert_ne!(clone.into_vec().as_ptr(), ptr);
+        assert_eq!(shared.into_vec().as_ptr(), ptr);
+    }
+}
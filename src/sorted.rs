@@ -0,0 +1,136 @@
+use std::ops::Deref;
+
+use crate::LocalStorageVec;
+
+/// A `LocalStorageVec` whose elements are always sorted.
+///
+/// Lookups are binary searches, insertions and removals are a binary search plus a shift. A
+/// vector created with [`SortedLocalStorageVec::deduplicated`] also never holds two equal
+/// elements, which makes it a small ordered set.
+#[derive(Debug, Clone)]
+pub struct SortedLocalStorageVec<T, const N: usize> {
+    // invariant: sorted, and without duplicates if `dedup`
+    vec: LocalStorageVec<T, N>,
+    dedup: bool,
+}
+
+impl<T: Ord + Default, const N: usize> SortedLocalStorageVec<T, N> {
+    /// An empty sorted vector that allows duplicate elements
+    pub fn new() -> Self {
+        Self {
+            vec: LocalStorageVec::new(),
+            dedup: false,
+        }
+    }
+
+    /// An empty sorted vector that ignores insertion of elements it already contains
+    pub fn deduplicated() -> Self {
+        Self {
+            vec: LocalStorageVec::new(),
+            dedup: true,
+        }
+    }
+
+    /// Inserts `value` at its sorted position, returning whether it was inserted.
+    ///
+    /// Only returns `false` for a deduplicated vector that already contains `value`. Equal
+    /// elements are kept in insertion order.
+    pub fn insert(&mut self, value: T) -> bool {
+        let index = self.vec.partition_point(|e| *e <= value);
+
+        if self.dedup && index > 0 && self.vec[index - 1] == value {
+            return false;
+        }
+
+        self.vec.insert(index, value);
+        true
+    }
+
+    /// Removes one element equal to `value`, returning whether one was present
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.vec.binary_search(value) {
+            Ok(index) => {
+                self.vec.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl<T: Ord, const N: usize> SortedLocalStorageVec<T, N> {
+    pub fn contains(&self, value: &T) -> bool {
+        self.vec.binary_search(value).is_ok()
+    }
+
+    /// Whether insertion ignores elements that are already present
+    pub fn is_deduplicated(&self) -> bool {
+        self.dedup
+    }
+
+    pub fn into_inner(self) -> LocalStorageVec<T, N> {
+        self.vec
+    }
+}
+
+impl<T: Ord + Default, const N: usize> Default for SortedLocalStorageVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Only shared access: mutating elements in place could break the ordering
+impl<T, const N: usize> Deref for SortedLocalStorageVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl<T: Ord + Default, const N: usize> Extend<T> for SortedLocalStorageVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord + Default, const N: usize> FromIterator<T> for SortedLocalStorageVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sorted = Self::new();
+        sorted.extend(iter);
+        sorted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stays_sorted() {
+        let mut sorted: SortedLocalStorageVec<u8, 4> = [3, 1, 2, 1].into_iter().collect();
+        assert_eq!(&sorted[..], &[1, 1, 2, 3]);
+
+        assert!(sorted.insert(0));
+        assert_eq!(&sorted[..], &[0, 1, 1, 2, 3]);
+
+        assert!(sorted.contains(&2));
+        assert!(sorted.remove(&1));
+        assert!(!sorted.remove(&7));
+        assert_eq!(&sorted[..], &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn deduplicated() {
+        let mut set: SortedLocalStorageVec<u8, 4> = SortedLocalStorageVec::deduplicated();
+
+        assert!(set.insert(2));
+        assert!(set.insert(1));
+        assert!(!set.insert(2));
+        assert!(set.insert(3));
+
+        assert_eq!(&set[..], &[1, 2, 3]);
+    }
+}
@@ -0,0 +1,387 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{btree_map, BTreeMap};
+use std::fmt;
+
+use crate::LocalStorageVec;
+
+/// A map that stores up to `N` key-value pairs inline, sorted by key, and moves them into a
+/// `BTreeMap` when it grows beyond that.
+///
+/// Unlike [`LocalStorageMap`](crate::LocalStorageMap), iteration is always in key order, also
+/// after spilling. Inline lookups are a binary search.
+#[derive(Clone)]
+pub struct LocalStorageSortedMap<K, V, const N: usize> {
+    repr: Repr<K, V, N>,
+}
+
+#[derive(Clone)]
+enum Repr<K, V, const N: usize> {
+    /// invariant: never holds more than `N` pairs, and the keys are unique and sorted
+    Inline(LocalStorageVec<(K, V), N>),
+    Heap(BTreeMap<K, V>),
+}
+
+impl<K: Default, V: Default, const N: usize> LocalStorageSortedMap<K, V, N> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline(LocalStorageVec::new()),
+        }
+    }
+}
+
+impl<K: Default, V: Default, const N: usize> Default for LocalStorageSortedMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The position of `key` in sorted `pairs`, or where it would be inserted
+fn search<K: Borrow<Q>, V, Q: Ord + ?Sized>(pairs: &[(K, V)], key: &Q) -> Result<usize, usize> {
+    pairs.binary_search_by(|(k, _)| k.borrow().cmp(key))
+}
+
+impl<K: Ord, V, const N: usize> LocalStorageSortedMap<K, V, N> {
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(pairs) => pairs.len(),
+            Repr::Heap(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self.repr {
+            Repr::Inline(pairs) => Some(&pairs[search(pairs, key).ok()?].1),
+            Repr::Heap(map) => map.get(key),
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &mut self.repr {
+            Repr::Inline(pairs) => {
+                let index = search(pairs, key).ok()?;
+                Some(&mut pairs[index].1)
+            }
+            Repr::Heap(map) => map.get_mut(key),
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// The pair with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// The pair with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next_back()
+    }
+}
+
+impl<K: Ord + Default, V: Default, const N: usize> LocalStorageSortedMap<K, V, N> {
+    /// Inserts a key-value pair, returning the old value if the key was already present
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match &mut self.repr {
+            Repr::Inline(pairs) => match search(pairs, &key) {
+                Ok(index) => Some(std::mem::replace(&mut pairs[index].1, value)),
+                Err(index) if pairs.len() < N => {
+                    pairs.insert(index, (key, value));
+                    None
+                }
+                Err(_) => self.spill().insert(key, value),
+            },
+            Repr::Heap(map) => map.insert(key, value),
+        }
+    }
+
+    /// Removes a key, returning its value if it was present
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &mut self.repr {
+            Repr::Inline(pairs) => {
+                let index = search(pairs, key).ok()?;
+                Some(pairs.remove(index).1)
+            }
+            Repr::Heap(map) => map.remove(key),
+        }
+    }
+
+    /// Keeps only the pairs for which `f` returns `true`
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        match &mut self.repr {
+            Repr::Inline(pairs) => pairs.retain_mut(|(k, v)| f(k, v)),
+            Repr::Heap(map) => map.retain(f),
+        }
+    }
+
+    /// Move the inline pairs into a `BTreeMap`, and return that map
+    fn spill(&mut self) -> &mut BTreeMap<K, V> {
+        if let Repr::Inline(pairs) = &mut self.repr {
+            let map = std::mem::take(pairs).into_iter().collect();
+            self.repr = Repr::Heap(map);
+        }
+
+        match &mut self.repr {
+            Repr::Heap(map) => map,
+            Repr::Inline(_) => unreachable!(),
+        }
+    }
+}
+
+impl<K, V, const N: usize> LocalStorageSortedMap<K, V, N> {
+    /// An iterator over the key-value pairs, in key order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        match &self.repr {
+            Repr::Inline(pairs) => Iter::Inline(pairs.iter()),
+            Repr::Heap(map) => Iter::Heap(map.iter()),
+        }
+    }
+
+    /// An iterator over the key-value pairs in key order, with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        match &mut self.repr {
+            Repr::Inline(pairs) => IterMut::Inline(pairs.iter_mut()),
+            Repr::Heap(map) => IterMut::Heap(map.iter_mut()),
+        }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.iter_mut().map(|(_, v)| v)
+    }
+}
+
+impl<K: Ord, V: PartialEq, const N: usize> PartialEq for LocalStorageSortedMap<K, V, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord, V: Eq, const N: usize> Eq for LocalStorageSortedMap<K, V, N> {}
+
+impl<K: Ord, V: PartialOrd, const N: usize> PartialOrd for LocalStorageSortedMap<K, V, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+/// Only the live entries are shown (in key order), not whether they are inline or spilled
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for LocalStorageSortedMap<K, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the key-value pairs of a [`LocalStorageSortedMap`], in key order
+pub enum Iter<'a, K, V> {
+    Inline(std::slice::Iter<'a, (K, V)>),
+    Heap(btree_map::Iter<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Inline(it) => it.next().map(|(k, v)| (k, v)),
+            Iter::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Inline(it) => it.size_hint(),
+            Iter::Heap(it) => it.size_hint(),
+        }
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Inline(it) => it.next_back().map(|(k, v)| (k, v)),
+            Iter::Heap(it) => it.next_back(),
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Iterator over the key-value pairs of a [`LocalStorageSortedMap`] in key order, with mutable
+/// values
+pub enum IterMut<'a, K, V> {
+    Inline(std::slice::IterMut<'a, (K, V)>),
+    Heap(btree_map::IterMut<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Inline(it) => it.next().map(|(k, v)| (&*k, v)),
+            IterMut::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IterMut::Inline(it) => it.size_hint(),
+            IterMut::Heap(it) => it.size_hint(),
+        }
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Inline(it) => it.next_back().map(|(k, v)| (&*k, v)),
+            IterMut::Heap(it) => it.next_back(),
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Owned iterator over the key-value pairs of a [`LocalStorageSortedMap`], in key order
+pub enum IntoIter<K, V, const N: usize> {
+    Inline(crate::IntoIter<(K, V), N>),
+    Heap(btree_map::IntoIter<K, V>),
+}
+
+impl<K, V, const N: usize> Iterator for IntoIter<K, V, N> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Inline(it) => it.next(),
+            IntoIter::Heap(it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IntoIter::Inline(it) => it.size_hint(),
+            IntoIter::Heap(it) => it.size_hint(),
+        }
+    }
+}
+
+impl<K, V, const N: usize> DoubleEndedIterator for IntoIter<K, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Inline(it) => it.next_back(),
+            IntoIter::Heap(it) => it.next_back(),
+        }
+    }
+}
+
+impl<K, V, const N: usize> ExactSizeIterator for IntoIter<K, V, N> {}
+
+impl<K, V, const N: usize> IntoIterator for LocalStorageSortedMap<K, V, N> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.repr {
+            Repr::Inline(pairs) => IntoIter::Inline(pairs.into_iter()),
+            Repr::Heap(map) => IntoIter::Heap(map.into_iter()),
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a LocalStorageSortedMap<K, V, N> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut LocalStorageSortedMap<K, V, N> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map: LocalStorageSortedMap<&str, u32, 2> = LocalStorageSortedMap::new();
+
+        assert_eq!(map.insert("b", 2), None);
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("a", 10), Some(1));
+        assert!(matches!(map.repr, Repr::Inline(_)));
+
+        *map.get_mut("b").unwrap() += 1;
+        assert_eq!(map.get("b"), Some(&3));
+        assert!(map.contains_key("a"));
+
+        assert_eq!(map.remove("a"), Some(10));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn ordered_in_both_modes() {
+        let mut map: LocalStorageSortedMap<u32, char, 3> = LocalStorageSortedMap::new();
+
+        for (k, v) in [(5, 'e'), (1, 'a'), (3, 'c')] {
+            map.insert(k, v);
+        }
+        assert!(matches!(map.repr, Repr::Inline(_)));
+        assert!(map.keys().eq(&[1, 3, 5]));
+
+        map.insert(2, 'b');
+        map.insert(4, 'd');
+        assert!(matches!(map.repr, Repr::Heap(_)));
+        assert!(map.keys().eq(&[1, 2, 3, 4, 5]));
+        assert_eq!(
+            format!("{map:?}"),
+            "{1: 'a', 2: 'b', 3: 'c', 4: 'd', 5: 'e'}"
+        );
+        assert_eq!(map.first_key_value(), Some((&1, &'a')));
+        assert_eq!(map.last_key_value(), Some((&5, &'e')));
+
+        map.retain(|k, _| k % 2 == 1);
+        let pairs: Vec<_> = map.into_iter().rev().collect();
+        assert_eq!(pairs, [(5, 'e'), (3, 'c'), (1, 'a')]);
+    }
+}
@@ -0,0 +1,96 @@
+//! Specialized `Extend` paths, like std's `SpecExtend` for `Vec`. Needs a nightly compiler and
+//! the `nightly` feature.
+
+use std::iter::TrustedLen;
+use std::slice;
+
+use crate::LocalStorageVec;
+
+pub(crate) trait SpecExtend<T, I> {
+    fn spec_extend(&mut self, iter: I);
+}
+
+impl<T: Default, I: Iterator<Item = T>, const N: usize> SpecExtend<T, I> for LocalStorageVec<T, N> {
+    default fn spec_extend(&mut self, iter: I) {
+        extend_pushing(self, iter);
+    }
+}
+
+/// The exact length is known up front: reserve once, then fill without capacity checks
+impl<T: Default, I: TrustedLen<Item = T>, const N: usize> SpecExtend<T, I>
+    for LocalStorageVec<T, N>
+{
+    fn spec_extend(&mut self, iter: I) {
+        let Some(additional) = iter.size_hint().1 else {
+            // more than `usize::MAX` elements (e.g. an infinite range): push until it runs out
+            // of capacity, or until the iterator panics
+            return extend_pushing(self, iter);
+        };
+
+        self.reserve(additional);
+
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                // the reserve guarantees that all elements fit
+                for (slot, value) in buf[*len..].iter_mut().zip(iter) {
+                    *slot = value;
+                    *len += 1;
+                }
+            }
+            LocalStorageVec::Heap(v) => v.extend(iter),
+        }
+
+        self.debug_check_invariants();
+    }
+}
+
+impl<'a, T: Copy + Default + 'a, I: Iterator<Item = &'a T>, const N: usize> SpecExtend<&'a T, I>
+    for LocalStorageVec<T, N>
+{
+    default fn spec_extend(&mut self, iter: I) {
+        self.spec_extend(iter.copied());
+    }
+}
+
+/// Copying from a slice is a bulk copy
+impl<'a, T: Copy + Default + 'a, const N: usize> SpecExtend<&'a T, slice::Iter<'a, T>>
+    for LocalStorageVec<T, N>
+{
+    fn spec_extend(&mut self, iter: slice::Iter<'a, T>) {
+        self.extend_from_slice(iter.as_slice());
+    }
+}
+
+fn extend_pushing<T: Default, const N: usize>(
+    lsv: &mut LocalStorageVec<T, N>,
+    iter: impl Iterator<Item = T>,
+) {
+    for value in iter {
+        lsv.push(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::LocalStorageVec;
+
+    #[test]
+    fn trusted_len() {
+        let mut lsv: LocalStorageVec<u32, 4> = LocalStorageVec::new();
+        lsv.extend(0..3);
+        assert!(matches!(lsv, LocalStorageVec::Stack { len: 3, .. }));
+
+        lsv.extend((3..10).map(|x| x * 2));
+        assert!(matches!(lsv, LocalStorageVec::Heap(_)));
+        assert_eq!(&lsv[..], &[0, 1, 2, 6, 8, 10, 12, 14, 16, 18]);
+    }
+
+    #[test]
+    fn from_slice() {
+        let mut lsv: LocalStorageVec<u8, 4> = LocalStorageVec::new();
+        lsv.extend(&[1, 2]);
+        lsv.extend([3, 4, 5].iter());
+        lsv.extend([6, 7].iter().filter(|&&x| x > 6));
+        assert_eq!(&lsv[..], &[1, 2, 3, 4, 5, 7]);
+    }
+}
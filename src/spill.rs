@@ -0,0 +1,70 @@
+use std::sync::RwLock;
+
+/// Describes a vector moving its elements from the inline buffer to the heap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpillEvent {
+    /// the number of elements that were moved
+    pub len: usize,
+    /// `size_of::<T>()`
+    pub element_size: usize,
+    /// the inline capacity `N`
+    pub inline_capacity: usize,
+    /// the capacity of the new heap buffer
+    pub heap_capacity: usize,
+}
+
+static SPILL_HOOK: RwLock<Option<fn(&SpillEvent)>> = RwLock::new(None);
+
+/// Install a function that is called whenever any `LocalStorageVec` spills to the heap, e.g. to
+/// count spills and validate the choice of `N`. `None` removes the hook.
+///
+/// The hook is process-wide: the vector itself has no room for per-instance state. It runs after
+/// the spill has completed, so a panicking hook leaves the vector intact.
+pub fn set_spill_hook(hook: Option<fn(&SpillEvent)>) {
+    *SPILL_HOOK.write().unwrap_or_else(|e| e.into_inner()) = hook;
+}
+
+pub(crate) fn report(event: SpillEvent) {
+    // copy the hook out, so the lock isn't held while it runs
+    let hook = *SPILL_HOOK.read().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(hook) = hook {
+        hook(&event);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::LocalStorageVec;
+
+    #[test]
+    fn spill_hook() {
+        static SPILLS: AtomicUsize = AtomicUsize::new(0);
+
+        // other tests spill concurrently, so only count this test's (unusual) vector type
+        fn hook(event: &SpillEvent) {
+            if event.element_size == 7 && event.inline_capacity == 3 {
+                assert_eq!((event.len, event.heap_capacity), (3, 4));
+                SPILLS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        set_spill_hook(Some(hook));
+
+        let mut lsv: LocalStorageVec<[u8; 7], 3> = LocalStorageVec::new();
+        lsv.extend([[0; 7]; 3]);
+        assert_eq!(SPILLS.load(Ordering::Relaxed), 0);
+
+        lsv.reserve(1);
+        assert_eq!(SPILLS.load(Ordering::Relaxed), 1);
+
+        // a vector that's already on the heap doesn't spill again
+        lsv.extend([[0; 7]; 10]);
+        assert_eq!(SPILLS.load(Ordering::Relaxed), 1);
+
+        set_spill_hook(None);
+    }
+}
@@ -0,0 +1,513 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+use crate::{FromUtf8Error, LocalStorageVec};
+
+/// A UTF-8 string that stores up to `N` bytes inline, and moves to the heap when it grows beyond
+/// that.
+///
+/// The bytes are kept in a `LocalStorageVec<u8, N>`, so the spill behavior is exactly that of the
+/// vector; once spilled, the heap buffer is the same allocation a `String` would own.
+#[derive(Clone, Default)]
+pub struct LocalStorageString<const N: usize> {
+    // invariant: always valid UTF-8
+    vec: LocalStorageVec<u8, N>,
+}
+
+impl<const N: usize> LocalStorageString<N> {
+    pub fn new() -> Self {
+        Self {
+            vec: LocalStorageVec::new(),
+        }
+    }
+
+    /// An empty `LocalStorageString` which has space for `capacity` bytes
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            vec: LocalStorageVec::with_capacity(capacity),
+        }
+    }
+
+    /// Reinterprets the bytes as a string, without copying. If they are not valid UTF-8, the
+    /// error gives the vector back.
+    pub fn from_utf8(vec: LocalStorageVec<u8, N>) -> Result<Self, FromUtf8Error<N>> {
+        match std::str::from_utf8(&vec) {
+            Ok(_) => Ok(Self { vec }),
+            Err(error) => Err(FromUtf8Error::new(vec, error)),
+        }
+    }
+
+    /// The underlying bytes, without copying
+    pub fn into_bytes(self) -> LocalStorageVec<u8, N> {
+        self.vec
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn push_str(&mut self, string: &str) {
+        self.vec.extend_from_slice(string.as_bytes());
+    }
+
+    pub fn as_str(&self) -> &str {
+        #[cfg(not(feature = "safe-only"))]
+        // SAFETY: the bytes are always valid UTF-8
+        let s = unsafe { self.vec.as_str_unchecked() };
+        #[cfg(feature = "safe-only")]
+        let s = self.vec.as_str().expect("the bytes are always valid UTF-8");
+        s
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        #[cfg(not(feature = "safe-only"))]
+        // SAFETY: the bytes are always valid UTF-8, and `str` only allows UTF-8 preserving edits
+        let s = unsafe { self.vec.as_str_mut_unchecked() };
+        #[cfg(feature = "safe-only")]
+        let s = self
+            .vec
+            .as_str_mut()
+            .expect("the bytes are always valid UTF-8");
+        s
+    }
+
+    /// The length in bytes
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// The capacity in bytes
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Removes the last character and returns it, or `None` if the string is empty
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        self.vec.truncate(self.len() - ch.len_utf8());
+
+        Some(ch)
+    }
+
+    /// Shortens the string to `new_len` bytes.
+    ///
+    /// Has no effect if `new_len >= len`. Panics if `new_len` is not on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(self.is_char_boundary(new_len));
+            self.vec.truncate(new_len);
+        }
+    }
+
+    /// Inserts `ch` at byte position `idx`.
+    ///
+    /// Panics if `idx > len` or if it is not on a char boundary.
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.insert_str(idx, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Inserts `string` at byte position `idx`.
+    ///
+    /// Panics if `idx > len` or if it is not on a char boundary.
+    pub fn insert_str(&mut self, idx: usize, string: &str) {
+        assert!(self.is_char_boundary(idx));
+
+        self.vec.extend_from_slice(string.as_bytes());
+        self.vec[idx..].rotate_right(string.len());
+    }
+
+    /// Removes and returns the character at byte position `idx`.
+    ///
+    /// Panics if `idx >= len` or if it is not on a char boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let ch = match self[idx..].chars().next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+
+        let n = ch.len_utf8();
+        self.vec[idx..].rotate_left(n);
+        self.vec.truncate(self.len() - n);
+
+        ch
+    }
+}
+
+impl<const N: usize> Deref for LocalStorageString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> DerefMut for LocalStorageString<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for LocalStorageString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for LocalStorageString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// Formatting into a `LocalStorageString` only allocates when the result no longer fits inline
+impl<const N: usize> fmt::Write for LocalStorageString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<const N: usize> From<&str> for LocalStorageString<N> {
+    fn from(value: &str) -> Self {
+        let mut s = Self::with_capacity(value.len());
+        s.push_str(value);
+        s
+    }
+}
+
+/// Strings that fit are copied inline; longer strings keep their heap buffer without copying
+impl<const N: usize> From<String> for LocalStorageString<N> {
+    fn from(value: String) -> Self {
+        if value.len() <= N {
+            Self::from(value.as_str())
+        } else {
+            Self {
+                vec: LocalStorageVec::Heap(value.into_bytes()),
+            }
+        }
+    }
+}
+
+/// A spilled string hands over its heap buffer without copying
+impl<const N: usize> From<LocalStorageString<N>> for String {
+    fn from(value: LocalStorageString<N>) -> Self {
+        match value.vec {
+            #[cfg(not(feature = "safe-only"))]
+            // SAFETY: the bytes are always valid UTF-8
+            LocalStorageVec::Heap(v) => unsafe { String::from_utf8_unchecked(v) },
+            #[cfg(feature = "safe-only")]
+            LocalStorageVec::Heap(v) => {
+                String::from_utf8(v).expect("the bytes are always valid UTF-8")
+            }
+            LocalStorageVec::Stack { .. } => String::from(value.as_str()),
+        }
+    }
+}
+
+impl<const N: usize> From<LocalStorageString<N>> for LocalStorageVec<u8, N> {
+    fn from(value: LocalStorageString<N>) -> Self {
+        value.into_bytes()
+    }
+}
+
+impl<const N: usize> FromStr for LocalStorageString<N> {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl<const N: usize> Extend<char> for LocalStorageString<N> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for ch in iter {
+            self.push(ch);
+        }
+    }
+}
+
+impl<'a, const N: usize> Extend<&'a str> for LocalStorageString<N> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<char> for LocalStorageString<N> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut s = Self::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<'a, const N: usize> FromIterator<&'a str> for LocalStorageString<N> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut s = Self::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<const N: usize> AsRef<str> for LocalStorageString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for LocalStorageString<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+// `Hash`, `Eq` and `Ord` all agree with `str`, so a `LocalStorageString` key can be looked up by `&str`
+impl<const N: usize> Borrow<str> for LocalStorageString<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Hash for LocalStorageString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> PartialEq for LocalStorageString<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for LocalStorageString<N> {}
+
+impl<const N: usize> PartialOrd for LocalStorageString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for LocalStorageString<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+macro_rules! impl_str_comparisons {
+    ($($other:ty),*) => {
+        $(
+            impl<const N: usize> PartialEq<$other> for LocalStorageString<N> {
+                fn eq(&self, other: &$other) -> bool {
+                    self.as_str() == &other[..]
+                }
+            }
+
+            impl<const N: usize> PartialEq<LocalStorageString<N>> for $other {
+                fn eq(&self, other: &LocalStorageString<N>) -> bool {
+                    &self[..] == other.as_str()
+                }
+            }
+
+            impl<const N: usize> PartialOrd<$other> for LocalStorageString<N> {
+                fn partial_cmp(&self, other: &$other) -> Option<Ordering> {
+                    self.as_str().partial_cmp(&other[..])
+                }
+            }
+
+            impl<const N: usize> PartialOrd<LocalStorageString<N>> for $other {
+                fn partial_cmp(&self, other: &LocalStorageString<N>) -> Option<Ordering> {
+                    self[..].partial_cmp(other.as_str())
+                }
+            }
+        )*
+    };
+}
+
+impl_str_comparisons!(str, &str, String);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_and_spill() {
+        let mut s: LocalStorageString<4> = LocalStorageString::new();
+
+        s.push_str("ab");
+        s.push('ç');
+        assert_eq!(s.as_str(), "abç");
+        assert_eq!(s.len(), 4);
+        assert!(matches!(s.vec, LocalStorageVec::Stack { .. }));
+
+        s.push('!');
+        assert_eq!(s.as_str(), "abç!");
+        assert!(matches!(s.vec, LocalStorageVec::Heap(_)));
+
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn from_utf8_and_into_bytes() {
+        let bytes: LocalStorageVec<u8, 4> = LocalStorageVec::from("abç");
+        let s = LocalStorageString::from_utf8(bytes).unwrap();
+        assert_eq!(s, "abç");
+        assert!(matches!(s.vec, LocalStorageVec::Stack { .. }));
+
+        let bytes = s.into_bytes();
+        assert_eq!(bytes.as_slice(), "abç".as_bytes());
+
+        let invalid: LocalStorageVec<u8, 4> = LocalStorageVec::from([b'a', 0xff]);
+        let error = LocalStorageString::from_utf8(invalid).unwrap_err();
+        assert_eq!(error.utf8_error().valid_up_to(), 1);
+        assert_eq!(error.into_bytes().as_slice(), [b'a', 0xff]);
+    }
+
+    #[test]
+    fn deref_and_format() {
+        let mut s: LocalStorageString<8> = LocalStorageString::new();
+        s.push_str("Hi there");
+
+        assert!(s.starts_with("Hi"));
+        s.make_ascii_lowercase();
+
+        assert_eq!(format!("{s}"), "hi there");
+        assert_eq!(format!("{s:?}"), "\"hi there\"");
+    }
+
+    #[test]
+    fn write_formatted() {
+        use std::fmt::Write;
+
+        let mut s: LocalStorageString<8> = LocalStorageString::new();
+
+        let x = 42;
+        write!(s, "x={x}").unwrap();
+        assert_eq!(s, "x=42");
+        assert!(matches!(s.vec, LocalStorageVec::Stack { .. }));
+
+        write!(s, ", y={}", 7).unwrap();
+        assert_eq!(s, "x=42, y=7");
+        assert!(matches!(s.vec, LocalStorageVec::Heap(_)));
+    }
+
+    #[test]
+    fn conversions() {
+        let s: LocalStorageString<4> = "abc".into();
+        assert!(matches!(s.vec, LocalStorageVec::Stack { .. }));
+        assert_eq!(String::from(s), "abc");
+
+        let long = String::from("abcdef");
+        let ptr = long.as_ptr();
+        let s: LocalStorageString<4> = long.into();
+        let back: String = s.into();
+        assert_eq!(back, "abcdef");
+        assert_eq!(back.as_ptr(), ptr);
+
+        let s: LocalStorageString<4> = "xy".parse().unwrap();
+        assert_eq!(s, "xy");
+    }
+
+    #[test]
+    fn iterators() {
+        let s: LocalStorageString<8> = "hello".chars().rev().collect();
+        assert_eq!(s, "olleh");
+
+        let mut s: LocalStorageString<8> = ["a", "b"].into_iter().collect();
+        s.extend(['c', 'd']);
+        s.extend(["ef", "g"]);
+        assert_eq!(s, "abcdefg");
+    }
+
+    #[test]
+    fn comparisons() {
+        let mut s: LocalStorageString<8> = LocalStorageString::new();
+        s.push_str("b");
+
+        assert_eq!(s, "b");
+        assert_eq!("b", s);
+        assert_eq!(s, String::from("b"));
+        assert!(s < "c");
+        let a = String::from("a");
+        assert!(a < s);
+    }
+
+    #[test]
+    fn map_key() {
+        let mut s: LocalStorageString<8> = LocalStorageString::new();
+        s.push_str("key");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(s, 42);
+
+        assert_eq!(map.get("key"), Some(&42));
+    }
+
+    #[test]
+    fn editing() {
+        let mut s: LocalStorageString<8> = "aé".into();
+
+        s.insert(1, 'ß');
+        s.insert_str(0, ">");
+        assert_eq!(s, ">aßé");
+
+        assert_eq!(s.remove(2), 'ß');
+        assert_eq!(s.pop(), Some('é'));
+        assert_eq!(s, ">a");
+
+        s.truncate(1);
+        assert_eq!(s, ">");
+        assert_eq!(s.pop(), Some('>'));
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncate_not_on_char_boundary() {
+        let mut s: LocalStorageString<8> = "é".into();
+        s.truncate(1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_not_on_char_boundary() {
+        let mut s: LocalStorageString<8> = "é".into();
+        s.insert(1, 'a');
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_not_on_char_boundary() {
+        let mut s: LocalStorageString<8> = "é".into();
+        s.remove(1);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let s: LocalStorageString<4> = LocalStorageString::with_capacity(16);
+        assert!(s.capacity() >= 16);
+
+        let s: LocalStorageString<4> = LocalStorageString::with_capacity(2);
+        assert_eq!(s.capacity(), 4);
+    }
+}
@@ -0,0 +1,102 @@
+//! Kani proofs for the core operations, run with `cargo kani`.
+//!
+//! The inline storage is a fully initialized `[T; N]`, so the proofs check bounds, lengths and
+//! storage modes, plus the unsafe spare-capacity API.
+
+use crate::{IndexError, LocalStorageVec, TryInsertError};
+
+const N: usize = 4;
+
+/// An arbitrary vector with up to `N + 2` elements, so both storage modes are covered
+fn any_vec() -> LocalStorageVec<u8, N> {
+    let len: usize = kani::any();
+    kani::assume(len <= N + 2);
+
+    let mut lsv = LocalStorageVec::new();
+    for _ in 0..len {
+        lsv.push(kani::any());
+    }
+
+    lsv
+}
+
+#[kani::proof]
+#[kani::unwind(8)]
+fn push_pop() {
+    let mut lsv = any_vec();
+    let len = lsv.len();
+    let inline = matches!(lsv, LocalStorageVec::Stack { .. });
+    assert_eq!(inline, len <= N);
+
+    let value: u8 = kani::any();
+    lsv.push(value);
+    lsv.check_invariants();
+    assert_eq!(lsv.len(), len + 1);
+    assert_eq!(lsv[len], value);
+
+    assert_eq!(lsv.pop(), Some(value));
+    assert_eq!(lsv.len(), len);
+}
+
+#[kani::proof]
+#[kani::unwind(8)]
+fn insert_remove() {
+    let mut lsv = any_vec();
+    let len = lsv.len();
+
+    let index: usize = kani::any();
+    kani::assume(index <= len);
+
+    let value: u8 = kani::any();
+    lsv.insert(index, value);
+    lsv.check_invariants();
+    assert_eq!(lsv.len(), len + 1);
+    assert_eq!(lsv[index], value);
+
+    assert_eq!(lsv.remove(index), value);
+    assert_eq!(lsv.len(), len);
+}
+
+#[kani::proof]
+#[kani::unwind(8)]
+fn checked_api_never_panics() {
+    let mut lsv = any_vec();
+    let len = lsv.len();
+    let capacity = lsv.capacity();
+    let index: usize = kani::any();
+
+    match lsv.try_insert(index, kani::any()) {
+        Ok(()) => assert!(index <= len && len < capacity),
+        Err(TryInsertError::Index(error, _)) => assert_eq!(error, IndexError { index, len }),
+        Err(TryInsertError::Capacity(_)) => assert!(len == capacity),
+    }
+
+    let len = lsv.len();
+    assert_eq!(lsv.try_remove(index).is_ok(), index < len);
+    assert_eq!(lsv.checked_index(index).is_ok(), index < lsv.len());
+}
+
+#[cfg(not(feature = "safe-only"))]
+#[kani::proof]
+#[kani::unwind(8)]
+fn spare_capacity() {
+    let mut lsv = any_vec();
+    let len = lsv.len();
+    let capacity = lsv.capacity();
+
+    let extra: usize = kani::any();
+    kani::assume(extra <= capacity - len);
+
+    // SAFETY: the spare slots that are touched are written, not de-initialized
+    let (live, spare) = unsafe { lsv.split_at_spare_mut() };
+    assert_eq!(live.len() + spare.len(), capacity);
+
+    for slot in &mut spare[..extra] {
+        slot.write(kani::any());
+    }
+
+    // SAFETY: the elements up to `len + extra` were written above
+    unsafe { lsv.set_len(len + extra) };
+    lsv.check_invariants();
+    assert_eq!(lsv.len(), len + extra);
+}